@@ -0,0 +1,376 @@
+use chrono::{Datelike, Days, NaiveDate};
+use rust_decimal::{Decimal, MathematicalOps};
+
+use crate::exchange_store::{DatedRate, RateStatus};
+
+/// Days a year the ECB publishes rates on, give or take the odd holiday.
+/// Daily volatility is scaled by its square root to annualize it.
+pub const PUBLISHED_DAYS_PER_YEAR: u32 = 252;
+
+/// Descriptive statistics of a currency pair over a range of dates. Only
+/// the rates the ECB published count, since interpolated ones would smooth
+/// the series out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateStats {
+    /// First date of the requested range
+    pub start: NaiveDate,
+    /// Last date of the requested range
+    pub end: NaiveDate,
+    /// How many published rates there are in the range
+    pub observations: usize,
+    /// The earliest published rate in the range
+    pub first: DatedRate,
+    /// The latest published rate in the range
+    pub last: DatedRate,
+    /// The lowest rate, on the earliest date it was seen
+    pub min: DatedRate,
+    /// The highest rate, on the earliest date it was seen
+    pub max: DatedRate,
+    pub mean: Decimal,
+    /// Sample standard deviation of the rates. `None` with a single rate.
+    pub std_dev: Option<Decimal>,
+    /// Sample standard deviation of the daily log returns, scaled to a year
+    /// of `PUBLISHED_DAYS_PER_YEAR`. e.g 0.08 for 8%. `None` with fewer than
+    /// 3 rates, since it takes 2 returns.
+    pub volatility: Option<Decimal>,
+    /// Change from `first` to `last` in percent, e.g -1.5 for -1.5%
+    pub change_percent: Decimal,
+}
+
+/// Change from `old` to `new` in percent, e.g 1.5 for 1.5%. `None` if `old`
+/// is 0.
+pub fn percent_change(old: Decimal, new: Decimal) -> Option<Decimal> {
+    (new - old)
+        .checked_div(old)
+        .map(|change| change * Decimal::ONE_HUNDRED)
+}
+
+/// Mean, and sample standard deviation of `values`. The deviation is `None`
+/// with fewer than 2 values.
+fn mean_and_std_dev(values: &[Decimal]) -> Option<(Decimal, Option<Decimal>)> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let count = Decimal::from(values.len());
+    let mean = values.iter().sum::<Decimal>() / count;
+    let std_dev = match values.len() {
+        0 | 1 => None,
+        _ => {
+            let squares: Decimal = values
+                .iter()
+                .map(|value| (value - mean) * (value - mean))
+                .sum();
+
+            (squares / (count - Decimal::ONE)).sqrt()
+        }
+    };
+
+    Some((mean, std_dev))
+}
+
+/// Statistics of the published rates of `series`, which is ordered by date.
+/// `None` if there's no published rate.
+pub(crate) fn stats(start: NaiveDate, end: NaiveDate, series: &[DatedRate]) -> Option<RateStats> {
+    let published: Vec<&DatedRate> = series
+        .iter()
+        .filter(|rate| rate.status == RateStatus::Observed)
+        .collect();
+    let rates: Vec<Decimal> = published.iter().map(|rate| rate.rate).collect();
+    let (mean, std_dev) = mean_and_std_dev(&rates)?;
+
+    let first = published.first()?;
+    let last = published.last()?;
+    // `min_by_key` would keep the last of equal rates
+    let min = published.iter().fold(
+        *first,
+        |min, rate| if rate.rate < min.rate { rate } else { min },
+    );
+    let max = published.iter().fold(
+        *first,
+        |max, rate| if rate.rate > max.rate { rate } else { max },
+    );
+
+    let log_returns: Vec<Decimal> = rates
+        .windows(2)
+        .filter_map(|pair| (pair[1] / pair[0]).checked_ln())
+        .collect();
+    let volatility = match log_returns.len() {
+        0 | 1 => None,
+        _ => mean_and_std_dev(&log_returns)
+            .and_then(|(_, std_dev)| std_dev)
+            .zip(Decimal::from(PUBLISHED_DAYS_PER_YEAR).sqrt())
+            .map(|(std_dev, days)| std_dev * days),
+    };
+
+    Some(RateStats {
+        start,
+        end,
+        observations: published.len(),
+        first: (*first).clone(),
+        last: (*last).clone(),
+        min: min.clone(),
+        max: max.clone(),
+        mean,
+        std_dev,
+        volatility,
+        change_percent: percent_change(first.rate, last.rate)?,
+    })
+}
+
+/// Simple moving average of the last `window` values, aligned with `values`.
+/// The first `window - 1` are `None`, since there aren't enough values yet.
+pub fn simple_moving_average(values: &[Decimal], window: usize) -> Vec<Option<Decimal>> {
+    if window == 0 {
+        return vec![None; values.len()];
+    }
+
+    let mut sum = Decimal::ZERO;
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            sum += value;
+            if index >= window {
+                sum -= values[index - window];
+            }
+
+            (index + 1 >= window).then(|| sum / Decimal::from(window))
+        })
+        .collect()
+}
+
+/// Exponential moving average over `window` values, aligned with `values`.
+/// Each value weighs `2 / (window + 1)`, and it starts from the simple
+/// average of the first `window` values, so the ones before are `None`.
+pub fn exponential_moving_average(values: &[Decimal], window: usize) -> Vec<Option<Decimal>> {
+    let weight = Decimal::TWO / Decimal::from(window + 1);
+    let mut previous: Option<Decimal> = None;
+
+    simple_moving_average(values, window)
+        .into_iter()
+        .zip(values)
+        .map(|(simple, value)| {
+            previous = match previous {
+                Some(previous) => Some(previous + weight * (value - previous)),
+                None => simple,
+            };
+
+            previous
+        })
+        .collect()
+}
+
+/// How a daily series is resampled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resample {
+    /// From Monday to Sunday
+    Weekly,
+    /// Calendar months
+    Monthly,
+}
+
+impl Resample {
+    /// The first, and last dates of the week, or month of `date`
+    fn bounds(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let start = match self {
+            Resample::Weekly => date - Days::new(date.weekday().num_days_from_monday().into()),
+            Resample::Monthly => date.with_day(1).expect("every month has a first day"),
+        };
+        let end = match self {
+            Resample::Weekly => start + Days::new(6),
+            Resample::Monthly => start
+                .checked_add_months(chrono::Months::new(1))
+                .and_then(|next| next.pred_opt())
+                .expect("dates are in range"),
+        };
+
+        (start, end)
+    }
+}
+
+/// The open, high, low, and close rates of a week, or month
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ohlc {
+    /// First day of the week, or month
+    pub start: NaiveDate,
+    /// Last day of the week, or month
+    pub end: NaiveDate,
+    /// The earliest rate in the week, or month
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    /// The latest rate in the week, or month
+    pub close: Decimal,
+    /// How many rates went into it
+    pub observations: usize,
+}
+
+/// Resamples a series ordered by date into weekly, or monthly OHLC rates.
+/// Interpolated rates count like any other, so leave them out first if they
+/// shouldn't. Weeks, and months without a rate are left out.
+pub fn resample(series: &[DatedRate], interval: Resample) -> Vec<Ohlc> {
+    let mut resampled: Vec<Ohlc> = Vec::new();
+
+    for rate in series {
+        match resampled.last_mut() {
+            Some(ohlc) if rate.date <= ohlc.end => {
+                ohlc.high = ohlc.high.max(rate.rate);
+                ohlc.low = ohlc.low.min(rate.rate);
+                ohlc.close = rate.rate;
+                ohlc.observations += 1;
+            }
+            _ => {
+                let (start, end) = interval.bounds(rate.date);
+
+                resampled.push(Ohlc {
+                    start,
+                    end,
+                    open: rate.rate,
+                    high: rate.rate,
+                    low: rate.rate,
+                    close: rate.rate,
+                    observations: 1,
+                });
+            }
+        }
+    }
+
+    resampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(rates: &[(&str, i64, RateStatus)]) -> Vec<DatedRate> {
+        rates
+            .iter()
+            .map(|(date, rate, status)| DatedRate {
+                date: date.parse().unwrap(),
+                rate: Decimal::new(*rate, 2),
+                status: *status,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_describes_a_series() {
+        let series = series(&[
+            ("2023-05-04", 100, RateStatus::Observed),
+            ("2023-05-05", 110, RateStatus::Observed),
+            ("2023-05-06", 500, RateStatus::Interpolated),
+            ("2023-05-08", 99, RateStatus::Observed),
+            ("2023-05-09", 110, RateStatus::Observed),
+        ]);
+        let start: NaiveDate = "2023-05-01".parse().unwrap();
+        let end: NaiveDate = "2023-05-31".parse().unwrap();
+
+        let stats = stats(start, end, &series).unwrap();
+
+        assert_eq!(4, stats.observations);
+        assert_eq!(series[3], stats.min);
+        // The first of the highest rates
+        assert_eq!(series[1], stats.max);
+        assert_eq!(Decimal::new(10475, 4), stats.mean);
+        assert_eq!(Decimal::TEN, stats.change_percent);
+        // Sample variance of 1.00, 1.10, 0.99, and 1.10 is 0.0036916..
+        assert_eq!(Decimal::new(6076, 5), stats.std_dev.unwrap().round_dp(5));
+        assert!(stats.volatility.unwrap() > Decimal::ONE);
+    }
+
+    #[test]
+    fn it_leaves_out_what_it_cannot_compute() {
+        let start: NaiveDate = "2023-05-01".parse().unwrap();
+        let single = series(&[
+            ("2023-05-04", 100, RateStatus::Observed),
+            ("2023-05-06", 100, RateStatus::Interpolated),
+        ]);
+
+        let stats = stats(start, start, &single).unwrap();
+        assert_eq!((None, None), (stats.std_dev, stats.volatility));
+        assert_eq!(Decimal::ZERO, stats.change_percent);
+
+        assert_eq!(None, super::stats(start, start, &single[1..]));
+        assert_eq!(None, percent_change(Decimal::ZERO, Decimal::ONE));
+    }
+
+    #[test]
+    fn it_computes_moving_averages() {
+        let values: Vec<Decimal> = [1, 2, 3, 4, 5].into_iter().map(Decimal::from).collect();
+
+        assert_eq!(
+            vec![
+                None,
+                None,
+                Some(Decimal::TWO),
+                Some(Decimal::from(3)),
+                Some(Decimal::from(4))
+            ],
+            simple_moving_average(&values, 3)
+        );
+        // Weighs 0.5, from the simple average of 2
+        assert_eq!(
+            vec![
+                None,
+                None,
+                Some(Decimal::TWO),
+                Some(Decimal::from(3)),
+                Some(Decimal::from(4))
+            ],
+            exponential_moving_average(&values, 3)
+        );
+        // Weighs 2/3, so 3 + 2/3 * (8 - 3)
+        let doubling: Vec<Decimal> = [2, 4, 8].into_iter().map(Decimal::from).collect();
+        assert_eq!(
+            vec![
+                None,
+                Some(Decimal::from(3)),
+                Some(Decimal::new(6_333_333, 6))
+            ],
+            exponential_moving_average(&doubling, 2)
+                .into_iter()
+                .map(|ema| ema.map(|ema| ema.round_dp(6)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![None; 5], simple_moving_average(&values, 0));
+        assert_eq!(vec![None; 5], simple_moving_average(&values, 6));
+    }
+
+    #[test]
+    fn it_resamples_into_weekly_and_monthly_rates() {
+        let series = series(&[
+            ("2023-04-27", 100, RateStatus::Observed),
+            ("2023-04-28", 90, RateStatus::Observed),
+            ("2023-05-02", 110, RateStatus::Observed),
+            ("2023-05-03", 120, RateStatus::Observed),
+            ("2023-05-04", 105, RateStatus::Observed),
+            ("2023-05-08", 101, RateStatus::Observed),
+        ]);
+
+        let weekly = resample(&series, Resample::Weekly);
+        assert_eq!(3, weekly.len());
+        assert_eq!(
+            Ohlc {
+                start: "2023-05-01".parse().unwrap(),
+                end: "2023-05-07".parse().unwrap(),
+                open: Decimal::new(110, 2),
+                high: Decimal::new(120, 2),
+                low: Decimal::new(105, 2),
+                close: Decimal::new(105, 2),
+                observations: 3,
+            },
+            weekly[1]
+        );
+
+        let monthly = resample(&series, Resample::Monthly);
+        assert_eq!(2, monthly.len());
+        assert_eq!(
+            (Decimal::new(100, 2), Decimal::new(90, 2)),
+            (monthly[0].open, monthly[0].close)
+        );
+        assert_eq!("2023-05-31", monthly[1].end.to_string());
+        assert_eq!(4, monthly[1].observations);
+    }
+}
@@ -1,14 +1,79 @@
-use std::path::PathBuf;
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
 
 use bytes::Bytes;
+use chrono::NaiveDate;
 use reqwest::{
     blocking::{Client, Response},
-    header::CONTENT_TYPE,
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE},
+    Certificate, Proxy, StatusCode,
 };
 use thiserror::Error;
 
 /// Where ECB stores their exchange rate history
-const ECB_HISTORY_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-hist.zip";
+const ECB_BASE_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref";
+
+/// Name of the ECB exchange rate history archive
+const ECB_HISTORY_ARCHIVE: &str = "eurofxref-hist.zip";
+
+/// Name of the only file expected inside the archive
+const ECB_HISTORY_CSV: &str = "eurofxref-hist.csv";
+
+/// Settings for the HTTP client used to download the ECB history.
+///
+/// The defaults download straight from the ECB with a 60 second timeout, and
+/// retry twice, doubling the wait between attempts starting from one second.
+#[derive(Clone, Debug)]
+pub struct SyncOptions {
+    /// Where the `eurofxref-hist.zip` archive is served from. Useful for
+    /// mirrors, or for testing against a local server.
+    pub base_url: String,
+    /// Proxy that all requests go through. e.g `http://proxy.corp:3128`
+    pub proxy: Option<String>,
+    /// Total time allowed for a single attempt, including reading the body.
+    /// `None` waits forever.
+    pub timeout: Option<Duration>,
+    /// How many more times to try after the first attempt fails
+    pub retries: u32,
+    /// How long to wait before the first retry. Doubles after each attempt.
+    pub backoff: Duration,
+    /// Overrides the `User-Agent` header
+    pub user_agent: Option<String>,
+    /// Extra headers sent along with the request
+    pub headers: Vec<(String, String)>,
+    /// PEM file with additional root certificates to trust, e.g for proxies
+    /// that intercept TLS.
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        SyncOptions {
+            base_url: ECB_BASE_URL.to_string(),
+            proxy: None,
+            timeout: Some(Duration::from_secs(60)),
+            retries: 2,
+            backoff: Duration::from_secs(1),
+            user_agent: Some(format!("moneyman/{}", env!("CARGO_PKG_VERSION"))),
+            headers: Vec::new(),
+            ca_bundle: None,
+        }
+    }
+}
+
+impl SyncOptions {
+    /// Full URL of the ECB history archive
+    pub fn history_url(&self) -> String {
+        format!(
+            "{}/{ECB_HISTORY_ARCHIVE}",
+            self.base_url.trim_end_matches('/')
+        )
+    }
+}
 
 /// Any error that may happen while downloading the ECB exchange rate history
 #[derive(Debug, Error)]
@@ -19,6 +84,52 @@ pub(crate) enum DownloadError {
     /// Failed to download the ECB history archive. May be due to rate limiting.
     #[error("failed to download archive")]
     Http(reqwest::Error),
+    /// The given `SyncOptions` can't be used to build an HTTP client
+    #[error("invalid HTTP client configuration: {0}")]
+    InvalidOptions(String),
+    /// The archive was downloaded but its contents don't look like the ECB
+    /// history. The local data is left untouched.
+    #[error("downloaded history is invalid: {0}")]
+    Invalid(ValidationError),
+    /// Failed to read the extracted history, or to write it to `data_dir`
+    #[error("failed to write the history to the data directory")]
+    Io(std::io::Error),
+}
+
+/// Reasons a downloaded history is rejected before it replaces the local copy
+#[derive(Debug, Error)]
+pub(crate) enum ValidationError {
+    /// The archive has no files, more than one file, or a file with another
+    /// name.
+    #[error("expected only {ECB_HISTORY_CSV} in the archive, found {0:?}")]
+    UnexpectedEntries(Vec<String>),
+    /// The first line isn't the `Date,...` header
+    #[error("header does not start with `Date`")]
+    MissingHeader,
+    /// There's a header, but no rows
+    #[error("history has no rows")]
+    Empty,
+    /// A row's first column isn't a `YYYY-MM-DD` date
+    #[error("line {line} has an invalid date {value:?}")]
+    InvalidDate { line: usize, value: String },
+    /// A row's date doesn't continue the order of the rows before it
+    #[error("line {line} is out of order")]
+    OutOfOrder { line: usize },
+    /// The new history has fewer rows than the one already on disk
+    #[error("history shrank from {old} rows to {new} rows")]
+    Shrunk { old: usize, new: usize },
+}
+
+impl From<ValidationError> for DownloadError {
+    fn from(err: ValidationError) -> Self {
+        DownloadError::Invalid(err)
+    }
+}
+
+impl From<std::io::Error> for DownloadError {
+    fn from(err: std::io::Error) -> Self {
+        DownloadError::Io(err)
+    }
 }
 
 impl From<reqwest::Error> for DownloadError {
@@ -33,38 +144,419 @@ impl From<zip::result::ZipError> for DownloadError {
     }
 }
 
-/// Downloads the latest ECB historical data, and unzips it to `data_dir`
-pub(crate) fn download_latest_history(data_dir: &PathBuf) -> Result<(), DownloadError> {
-    let client = Client::new()
-        .get(ECB_HISTORY_URL)
-        .header(CONTENT_TYPE, "application/zip");
+/// Builds the blocking HTTP client out of the sync options
+fn build_client(options: &SyncOptions) -> Result<Client, DownloadError> {
+    let mut builder = Client::builder().timeout(options.timeout);
+
+    if let Some(user_agent) = &options.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+
+    if let Some(proxy) = &options.proxy {
+        let proxy = Proxy::all(proxy)
+            .map_err(|_| DownloadError::InvalidOptions(format!("bad proxy URL {proxy}")))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(ca_bundle) = &options.ca_bundle {
+        let pem = std::fs::read(ca_bundle).map_err(|_| {
+            DownloadError::InvalidOptions(format!("can't read {}", ca_bundle.display()))
+        })?;
+        let certs = Certificate::from_pem_bundle(&pem).map_err(|_| {
+            DownloadError::InvalidOptions(format!("{} is not a PEM bundle", ca_bundle.display()))
+        })?;
 
-    let res: Response = client.send()?;
-    let content: Bytes = res.bytes()?;
-    let reader = std::io::Cursor::new(content.as_ref());
-    let mut zip = zip::ZipArchive::new(reader)?;
+        builder = certs
+            .into_iter()
+            .fold(builder, |builder, cert| builder.add_root_certificate(cert));
+    }
 
-    zip.extract(data_dir)?;
+    let headers =
+        options
+            .headers
+            .iter()
+            .try_fold(HeaderMap::new(), |mut headers, (name, value)| {
+                let name = HeaderName::try_from(name.as_str()).map_err(|_| {
+                    DownloadError::InvalidOptions(format!("bad header name {name}"))
+                })?;
+                let value = HeaderValue::try_from(value.as_str()).map_err(|_| {
+                    DownloadError::InvalidOptions(format!("bad header value {value}"))
+                })?;
+                headers.append(name, value);
+
+                Ok::<_, DownloadError>(headers)
+            })?;
+
+    builder
+        .default_headers(headers)
+        .build()
+        .map_err(|err| DownloadError::InvalidOptions(err.to_string()))
+}
+
+/// Whether it's worth trying the request again
+fn is_transient(err: &reqwest::Error) -> bool {
+    match err.status() {
+        Some(status) => {
+            status.is_server_error()
+                || status == StatusCode::TOO_MANY_REQUESTS
+                || status == StatusCode::REQUEST_TIMEOUT
+        }
+        None => err.is_timeout() || err.is_connect() || err.is_request() || err.is_body(),
+    }
+}
+
+/// Downloads the archive, retrying transient failures with exponential
+/// backoff.
+fn fetch_archive(client: &Client, options: &SyncOptions) -> Result<Bytes, DownloadError> {
+    let url = options.history_url();
+    let mut attempt = 0;
+
+    loop {
+        let result = client
+            .get(url.as_str())
+            .header(CONTENT_TYPE, "application/zip")
+            .send()
+            .and_then(Response::error_for_status)
+            .and_then(Response::bytes);
+
+        match result {
+            Ok(content) => return Ok(content),
+            Err(err) if attempt < options.retries && is_transient(&err) => {
+                thread::sleep(options.backoff.saturating_mul(2u32.saturating_pow(attempt)));
+                attempt += 1;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// Pulls the history CSV out of the archive. Reading the whole entry also
+/// verifies its checksum, so truncated archives are caught here.
+fn extract_history(content: &[u8]) -> Result<String, DownloadError> {
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(content))?;
+    let names: Vec<String> = zip.file_names().map(String::from).collect();
+
+    if names != [ECB_HISTORY_CSV] {
+        return Err(ValidationError::UnexpectedEntries(names).into());
+    }
+
+    let mut csv = String::new();
+    zip.by_index(0)?.read_to_string(&mut csv)?;
+
+    Ok(csv)
+}
+
+/// Checks that `csv` looks like the ECB history, and that it has at least as
+/// many rows as `previous_rows`. Returns the number of rows.
+fn validate_history(csv: &str, previous_rows: Option<usize>) -> Result<usize, ValidationError> {
+    let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+
+    match lines.next() {
+        Some(header) if header.starts_with("Date") => (),
+        _ => return Err(ValidationError::MissingHeader),
+    }
+
+    let mut dates: Vec<NaiveDate> = Vec::new();
+
+    for (index, row) in lines.enumerate() {
+        // Plus two since lines are one-indexed, and the header is skipped
+        let line = index + 2;
+        let value = row.split(',').next().unwrap_or_default().trim();
+        let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            ValidationError::InvalidDate {
+                line,
+                value: value.to_string(),
+            }
+        })?;
+
+        // ECB publishes newest first, but any consistent order is fine as
+        // long as there are no duplicates.
+        let in_order = match dates.as_slice() {
+            [] => true,
+            [first] => *first != date,
+            [first, second, ..] => {
+                let last = dates[dates.len() - 1];
+                (first > second && last > date) || (first < second && last < date)
+            }
+        };
+
+        if !in_order {
+            return Err(ValidationError::OutOfOrder { line });
+        }
+
+        dates.push(date);
+    }
+
+    match previous_rows {
+        _ if dates.is_empty() => Err(ValidationError::Empty),
+        Some(old) if old > dates.len() => Err(ValidationError::Shrunk {
+            old,
+            new: dates.len(),
+        }),
+        _ => Ok(dates.len()),
+    }
+}
+
+/// Counts the rows of the history already in `data_dir`, if there is one
+fn count_existing_rows(csv_path: &Path) -> Option<usize> {
+    std::fs::read_to_string(csv_path).ok().map(|csv| {
+        csv.lines()
+            .filter(|line| !line.trim().is_empty())
+            .count()
+            .saturating_sub(1)
+    })
+}
+
+/// Downloads the latest ECB historical data, and unzips it to `data_dir`.
+/// The existing history is only replaced once the new one passes validation.
+pub(crate) fn download_latest_history(
+    data_dir: &PathBuf,
+    options: &SyncOptions,
+) -> Result<(), DownloadError> {
+    let client = build_client(options)?;
+    let content: Bytes = fetch_archive(&client, options)?;
+    let csv = extract_history(content.as_ref())?;
+    let csv_path = data_dir.join(ECB_HISTORY_CSV);
+
+    validate_history(&csv, count_existing_rows(&csv_path))?;
+
+    // Write next to the old file, then swap, so a failure halfway never
+    // leaves a partial history behind.
+    let tmp_path = data_dir.join(format!("{ECB_HISTORY_CSV}.tmp"));
+    std::fs::create_dir_all(data_dir)?;
+    std::fs::write(&tmp_path, csv)?;
+    std::fs::rename(&tmp_path, &csv_path)?;
 
     Ok(())
 }
 
 #[cfg(test)]
-mod tests {
-    use rand::distributions::{Alphanumeric, DistString};
+pub(crate) mod tests {
+    use std::{io::Write, net::TcpListener};
 
     use super::*;
+    use crate::test_support::temp_data_dir;
+
+    /// Zips `csv` up as `eurofxref-hist.csv`
+    pub(crate) fn zip_history(csv: &str) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+
+        writer
+            .start_file("eurofxref-hist.csv", zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(csv.as_bytes()).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// Serves each of the `(status, body)` responses once, in order, on a
+    /// random local port. Returns the base URL to pass to `SyncOptions`.
+    pub(crate) fn serve(responses: Vec<(u16, Vec<u8>)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0; 4096];
+                let _ = stream.read(&mut buf);
+
+                let head = format!(
+                    "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+
+        format!("http://{addr}")
+    }
+
+    fn fast_options(base_url: String) -> SyncOptions {
+        SyncOptions {
+            base_url,
+            timeout: Some(Duration::from_secs(5)),
+            backoff: Duration::from_millis(10),
+            ..SyncOptions::default()
+        }
+    }
 
     #[test]
     fn it_downloads_ecb_history() {
-        let rand_str = Alphanumeric.sample_string(&mut rand::thread_rng(), 16);
-        let data_dir = PathBuf::new()
-            .join("/tmp")
-            .join(format!("moneyman_{}", rand_str));
+        let data_dir = temp_data_dir();
+
+        assert_eq!(
+            (),
+            download_latest_history(&data_dir, &SyncOptions::default()).unwrap()
+        );
+        assert!(data_dir.join("eurofxref-hist.csv").exists());
+    }
+
+    #[test]
+    fn it_downloads_from_the_base_url_override() {
+        let data_dir = temp_data_dir();
+        let csv = "Date,USD,\n2023-05-12,1.0892,\n";
+        let base_url = serve(vec![(200, zip_history(csv))]);
+
+        download_latest_history(&data_dir, &fast_options(base_url)).unwrap();
+
+        let extracted = std::fs::read_to_string(data_dir.join("eurofxref-hist.csv")).unwrap();
+        assert_eq!(csv, extracted);
+    }
 
-        std::fs::create_dir(&data_dir).expect("failed to create test directory");
+    #[test]
+    fn it_retries_transient_failures() {
+        let data_dir = temp_data_dir();
+        let csv = "Date,USD,\n2023-05-12,1.0892,\n";
+        let base_url = serve(vec![
+            (503, Vec::new()),
+            (429, Vec::new()),
+            (200, zip_history(csv)),
+        ]);
+
+        download_latest_history(&data_dir, &fast_options(base_url)).unwrap();
 
-        assert_eq!((), download_latest_history(&data_dir).unwrap());
         assert!(data_dir.join("eurofxref-hist.csv").exists());
     }
+
+    #[test]
+    fn it_gives_up_after_running_out_of_retries() {
+        let data_dir = temp_data_dir();
+        let base_url = serve(vec![(503, Vec::new()), (503, Vec::new())]);
+        let options = SyncOptions {
+            retries: 1,
+            ..fast_options(base_url)
+        };
+
+        match download_latest_history(&data_dir, &options) {
+            Err(DownloadError::Http(err)) => {
+                assert_eq!(Some(StatusCode::SERVICE_UNAVAILABLE), err.status())
+            }
+            other => panic!("expected an HTTP error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn it_times_out() {
+        let data_dir = temp_data_dir();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let options = SyncOptions {
+            timeout: Some(Duration::from_millis(200)),
+            retries: 0,
+            ..fast_options(format!("http://{}", listener.local_addr().unwrap()))
+        };
+
+        match download_latest_history(&data_dir, &options) {
+            Err(DownloadError::Http(err)) => assert!(err.is_timeout()),
+            other => panic!("expected a timeout, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn it_keeps_the_old_history_if_the_download_is_not_a_zip() {
+        let data_dir = temp_data_dir();
+        let old_csv = "Date,USD,\n2023-05-11,1.093,\n";
+        std::fs::write(data_dir.join("eurofxref-hist.csv"), old_csv).unwrap();
+        let base_url = serve(vec![(200, b"<html>Maintenance</html>".to_vec())]);
+
+        match download_latest_history(&data_dir, &fast_options(base_url)) {
+            Err(DownloadError::Unzip(_)) => (),
+            other => panic!("expected an unzip error, got {other:?}"),
+        }
+
+        let csv = std::fs::read_to_string(data_dir.join("eurofxref-hist.csv")).unwrap();
+        assert_eq!(old_csv, csv);
+    }
+
+    #[test]
+    fn it_rejects_truncated_archives() {
+        let mut archive = zip_history("Date,USD,\n2023-05-12,1.0892,\n");
+        archive.truncate(archive.len() / 2);
+
+        assert!(matches!(
+            extract_history(&archive),
+            Err(DownloadError::Unzip(_))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_archives_with_unexpected_entries() {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer
+            .start_file("index.html", zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(b"hello").unwrap();
+        let archive = writer.finish().unwrap().into_inner();
+
+        assert!(matches!(
+            extract_history(&archive),
+            Err(DownloadError::Invalid(ValidationError::UnexpectedEntries(
+                _
+            )))
+        ));
+    }
+
+    #[test]
+    fn it_validates_the_history() {
+        assert!(matches!(
+            validate_history("USD,JPY\n1.0,2.0\n", None),
+            Err(ValidationError::MissingHeader)
+        ));
+        assert!(matches!(
+            validate_history("Date,USD,\n", None),
+            Err(ValidationError::Empty)
+        ));
+        assert!(matches!(
+            validate_history("Date,USD,\n2023-05-12,1.0892,\n12/05/2023,1.0,\n", None),
+            Err(ValidationError::InvalidDate { line: 3, .. })
+        ));
+        assert!(matches!(
+            validate_history(
+                "Date,USD,\n2023-05-12,1.0,\n2023-05-11,1.0,\n2023-05-15,1.0,\n",
+                None
+            ),
+            Err(ValidationError::OutOfOrder { line: 4 })
+        ));
+        assert!(matches!(
+            validate_history("Date,USD,\n2023-05-12,1.0,\n2023-05-12,1.0,\n", None),
+            Err(ValidationError::OutOfOrder { line: 3 })
+        ));
+        assert!(matches!(
+            validate_history("Date,USD,\n2023-05-12,1.0,\n", Some(2)),
+            Err(ValidationError::Shrunk { old: 2, new: 1 })
+        ));
+        assert_eq!(
+            2,
+            validate_history("Date,USD,\n2023-05-12,1.0,\n2023-05-11,1.0,\n", Some(1)).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_old_history_if_the_new_one_is_shorter() {
+        let data_dir = temp_data_dir();
+        let old_csv = "Date,USD,\n2023-05-12,1.0892,\n2023-05-11,1.093,\n";
+        std::fs::write(data_dir.join("eurofxref-hist.csv"), old_csv).unwrap();
+        let base_url = serve(vec![(200, zip_history("Date,USD,\n2023-05-12,1.0892,\n"))]);
+
+        match download_latest_history(&data_dir, &fast_options(base_url)) {
+            Err(DownloadError::Invalid(ValidationError::Shrunk { .. })) => (),
+            other => panic!("expected the history to be rejected, got {other:?}"),
+        }
+
+        let csv = std::fs::read_to_string(data_dir.join("eurofxref-hist.csv")).unwrap();
+        assert_eq!(old_csv, csv);
+    }
+
+    #[test]
+    fn it_rejects_invalid_headers() {
+        let options = SyncOptions {
+            headers: vec![("bad header".to_string(), "value".to_string())],
+            ..SyncOptions::default()
+        };
+
+        assert!(matches!(
+            build_client(&options),
+            Err(DownloadError::InvalidOptions(_))
+        ));
+    }
 }
@@ -1,14 +1,32 @@
-use std::path::PathBuf;
+#[cfg(feature = "download")]
+use std::time::Duration;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
+#[cfg(any(feature = "download", test))]
+use chrono::{Datelike, Weekday};
 use rusqlite::Connection;
+use rust_decimal::Decimal;
 use rusty_money::{
     iso::{self, Currency},
     Exchange, ExchangeRate, Money,
 };
 use thiserror::Error;
 
-use crate::{ecb, persistence};
+#[cfg(feature = "download")]
+use crate::ecb::{self, DownloadError, SyncOptions};
+#[cfg(feature = "download")]
+use crate::persistence::seed::SeedError;
+use crate::{
+    analytics::{self, RateStats},
+    period::{self, AverageMethod, AverageRate, ClosingRate, InvalidPeriod, Period},
+    persistence,
+    pricing::{Pricing, Quote},
+    rounding::Rounding,
+};
 
 /// Represents the local data store of moneyman
 pub struct ExchangeStore {
@@ -18,9 +36,199 @@ pub struct ExchangeStore {
     data_dir: PathBuf,
 }
 
+/// A rate the European Central Bank corrected after it was first published
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Revision {
+    /// Date of the corrected observation
+    pub date: NaiveDate,
+    /// ISO alpha code of the currency. This isn't a `Currency` since ECB also
+    /// records currencies that no longer exist.
+    pub currency: String,
+    /// Rate of one EUR in `currency` before the correction. `None` if there
+    /// was no rate.
+    pub old: Option<Decimal>,
+    /// Rate of one EUR in `currency` after the correction. `None` if ECB
+    /// retracted the rate.
+    pub new: Option<Decimal>,
+}
+
+/// What changed in the local data store after a sync
+#[derive(Clone, Debug)]
+pub struct SyncReport {
+    /// The latest date with exchange rates
+    pub latest_date: NaiveDate,
+    /// Rates of already stored dates that ECB has since corrected, which
+    /// were updated in the local data store.
+    pub revisions: Vec<Revision>,
+}
+
+/// Whether a rate was published by the ECB
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateStatus {
+    /// Published by the ECB on that date
+    Observed,
+    /// Interpolated from the nearest dates with published rates
+    Interpolated,
+    /// There's no rate for the currency on that date, e.g it wasn't recorded
+    /// by ECB yet, or anymore.
+    Missing,
+}
+
+/// A rate of one unit of the base currency in another currency
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuotedRate {
+    /// ISO alpha code of the quoted currency. This isn't a `Currency` since
+    /// ECB also records currencies that no longer exist.
+    pub currency: String,
+    /// `None` if `status` is `RateStatus::Missing`
+    pub rate: Option<Decimal>,
+    pub status: RateStatus,
+}
+
+/// A rate of one unit of a currency in another on a date
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatedRate {
+    pub date: NaiveDate,
+    pub rate: Decimal,
+    /// Either `RateStatus::Observed`, or `RateStatus::Interpolated`. Dates
+    /// without a rate are left out instead.
+    pub status: RateStatus,
+}
+
+/// Every rate in the local data store on a date
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RatesOnDate {
+    pub date: NaiveDate,
+    /// ISO alpha code of the currency every rate is quoted against
+    pub base: &'static str,
+    /// Sorted by currency, and never includes the base currency itself
+    pub rates: Vec<QuotedRate>,
+}
+
+/// Whether the rates of a date were interpolated, and the rate of one EUR in
+/// every currency recorded, if there's any
+type AllRates = (bool, Vec<(String, Option<Decimal>)>);
+
+/// Rates of a set of currencies against each other on a date
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrossRates {
+    pub date: NaiveDate,
+    /// ISO alpha codes of the currencies, in the order they were asked for
+    pub currencies: Vec<&'static str>,
+    /// `rates[i][j]` is the rate of one unit of `currencies[i]` in
+    /// `currencies[j]`, so the diagonal is 1. `None` if either currency has
+    /// no rate on the date.
+    pub rates: Vec<Vec<Option<Decimal>>>,
+    /// Either `RateStatus::Observed`, or `RateStatus::Interpolated` if the
+    /// rates of the whole date were interpolated
+    pub status: RateStatus,
+}
+
+/// The first and last dates ECB published a rate for a currency
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CurrencyCoverage {
+    /// ISO alpha code of the currency. This isn't a `Currency` since ECB also
+    /// records currencies that no longer exist.
+    pub currency: String,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+}
+
+/// A summary of what's in the local data store, and how fresh it is
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoreStats {
+    /// Directory of the local data store
+    pub data_dir: PathBuf,
+    /// Size of the SQLite database in bytes
+    pub db_size: u64,
+    /// First date with rates published by ECB. `None` if the store is empty.
+    pub first_date: Option<NaiveDate>,
+    /// Latest date with rates published by ECB. `None` if the store is empty.
+    pub latest_date: Option<NaiveDate>,
+    /// Dates with rates published by ECB
+    pub observed_rows: usize,
+    /// Dates with rates interpolated from the neighboring observations
+    pub interpolated_rows: usize,
+    /// Every currency with at least one published rate, sorted by currency
+    pub currencies: Vec<CurrencyCoverage>,
+    /// When the store was last synced. `None` for stores last synced before
+    /// this was recorded.
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Where the ECB history was last downloaded from. `None` for stores last
+    /// synced before this was recorded.
+    pub source_url: Option<String>,
+}
+
+/// When `ExchangeStore::open_with_policy` syncs with the European Central
+/// Bank before opening the local data store.
+///
+/// The default only syncs a store that has never been synced, just like
+/// calling `ExchangeStore::sync` the first time.
+#[cfg(feature = "download")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncPolicy {
+    /// Syncs once the latest date with rates is more than this many business
+    /// days behind today. `None` never syncs an existing store.
+    pub max_business_days_behind: Option<u32>,
+    /// How long to wait after a sync was attempted before attempting another
+    /// one, whether it succeeded or not. Keeps a stale store, e.g during a
+    /// holiday, or an outage, from hitting the ECB on every open.
+    pub min_attempt_interval: Duration,
+    /// Never touches the network. Opens the existing store as is, and fails
+    /// if there's none.
+    pub offline: bool,
+}
+
+#[cfg(feature = "download")]
+impl Default for SyncPolicy {
+    fn default() -> Self {
+        SyncPolicy {
+            max_business_days_behind: None,
+            min_attempt_interval: Duration::from_secs(60 * 60),
+            offline: false,
+        }
+    }
+}
+
+/// What `ExchangeStore::open_with_policy` did before opening the store
+#[cfg(feature = "download")]
+#[derive(Debug)]
+pub enum SyncOutcome {
+    /// The store is fresh enough, or the policy never syncs an existing one
+    UpToDate,
+    /// The store was synced
+    Synced(SyncReport),
+    /// The store is stale, but a sync was attempted too recently
+    Throttled,
+    /// The store is stale, but the policy is offline
+    Offline,
+    /// The store is stale, and syncing failed. The existing data was opened
+    /// as is.
+    Failed(SyncError),
+}
+
+/// How `ExchangeStore::convert_on_date_with_options` converts. The default
+/// is the same as `ExchangeStore::convert_on_date`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConversionOptions {
+    /// Uses interpolated rates if the date isn't on record
+    pub fallback: bool,
+    /// How the converted amount is rounded. Exact by default.
+    pub rounding: Rounding,
+}
+
+/// The result of converting to each currency in `convert_to_many`, keyed by
+/// ISO alpha code
+pub type ConversionResults<'c> =
+    HashMap<&'static str, Result<Money<'c, Currency>, ConversionError>>;
+
 /// Possible errors that may occur when syncing the local data store with the
 /// European Central Bank's history.
+///
+/// Some variants only exist with the `download` feature, so the enum is
+/// non-exhaustive.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum SyncError {
     /// If the ECB history (CSV) isn't present in the same directory as the
     /// local data store.
@@ -32,9 +240,24 @@ pub enum SyncError {
     /// Failed to seed the local data store
     #[error("unable to complete seeding the exchange store")]
     Seed,
+    /// Missing rates couldn't be interpolated from their neighboring dates
+    #[error("unable to interpolate missing rates: {0}")]
+    Interpolation(String),
     /// Unable to download the latest exchange history from the ECB
+    #[cfg(feature = "download")]
     #[error("failed to download currency exchange history from ECB")]
     Download,
+    /// The `SyncOptions` given can't be used, e.g a malformed proxy URL or an
+    /// unreadable CA bundle.
+    #[cfg(feature = "download")]
+    #[error("invalid sync options: {0}")]
+    InvalidOptions(String),
+    /// The downloaded history failed validation, e.g an HTML error page
+    /// instead of an archive, or fewer rows than before. The existing local
+    /// data is kept as is.
+    #[cfg(feature = "download")]
+    #[error("rejected the downloaded ECB history: {0}")]
+    InvalidHistory(String),
 }
 
 /// Possible errors that may happen when attempting to read the local data store
@@ -45,7 +268,10 @@ pub enum InitError {
     CouldNotRead,
 }
 
-#[derive(Debug, Error)]
+/// Possible errors that may happen when converting, or looking up rates.
+/// New kinds of errors may be added, so the enum is non-exhaustive.
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
 pub enum ConversionError {
     /// Unable to parse the data from the store due to it potentially having
     /// an unexpected format.
@@ -63,23 +289,132 @@ pub enum ConversionError {
     /// If they're trying to convert a currency to itself
     #[error("there's no need to convert anything. it's the same currency.")]
     SameCurrency,
+    /// The fees of a quote are more than the amount being converted
+    #[error("the fees are more than the amount being converted")]
+    FeesExceedAmount,
+    /// There's not a single rate between the two dates, including both
+    #[error("could not find any exchange rate from {0} to {1}")]
+    NoExchangeRates(NaiveDate, NaiveDate),
+    /// The period doesn't exist, e.g month 13
+    #[error(transparent)]
+    InvalidPeriod(#[from] InvalidPeriod),
 }
 
 impl ExchangeStore {
     /// Syncs the local data store's currency exchange data with the European
-    /// Central Bank.
-    pub fn sync(data_dir: PathBuf) -> Result<(Self, NaiveDate), SyncError> {
-        ecb::download_latest_history(&data_dir).map_err(|_e| SyncError::Download)?;
+    /// Central Bank. `options` controls how the history is downloaded, like
+    /// the proxy, timeout, and retries. See `SyncOptions::default()`.
+    ///
+    /// Dates that were already stored are compared against the new history,
+    /// and any rates ECB has since corrected are updated and reported in the
+    /// `SyncReport`.
+    #[cfg(feature = "download")]
+    pub fn sync(data_dir: PathBuf, options: &SyncOptions) -> Result<(Self, SyncReport), SyncError> {
+        ecb::download_latest_history(&data_dir, options).map_err(|err| match err {
+            DownloadError::InvalidOptions(reason) => SyncError::InvalidOptions(reason),
+            err @ (DownloadError::Invalid(_) | DownloadError::Unzip(_)) => {
+                SyncError::InvalidHistory(err.to_string())
+            }
+            _ => SyncError::Download,
+        })?;
 
         let db_path = data_dir.join("eurofxref-hist.db3");
         let conn = Connection::open(db_path).map_err(|_| SyncError::CouldNotRead)?;
         let store = ExchangeStore { conn, data_dir };
 
-        persistence::seed::seed_db(&store.conn, &store.data_dir).map_err(|_e| SyncError::Seed)?;
+        let revisions =
+            persistence::seed::seed_db(&store.conn, &store.data_dir).map_err(|err| match err {
+                SeedError::Interpolation(err) => SyncError::Interpolation(err.to_string()),
+                SeedError::Sqlite(_) => SyncError::Seed,
+            })?;
+
+        persistence::metadata::set(
+            &store.conn,
+            persistence::metadata::LAST_SYNCED_AT,
+            &Utc::now().to_rfc3339(),
+        )
+        .and_then(|_| {
+            persistence::metadata::set(
+                &store.conn,
+                persistence::metadata::SOURCE_URL,
+                &options.history_url(),
+            )
+        })
+        .map_err(|_e| SyncError::Seed)?;
 
         let latest_date = store.get_latest_date().ok_or(SyncError::CouldNotRead)?;
 
-        Ok((store, latest_date))
+        Ok((
+            store,
+            SyncReport {
+                latest_date,
+                revisions,
+            },
+        ))
+    }
+
+    /// Opens the local data store, syncing it first if `policy` says it's
+    /// missing, or stale. See `SyncPolicy`.
+    ///
+    /// Only a missing store fails to open when syncing fails. A stale one is
+    /// opened as is, with the error in `SyncOutcome::Failed`.
+    #[cfg(feature = "download")]
+    pub fn open_with_policy(
+        data_dir: PathBuf,
+        options: &SyncOptions,
+        policy: &SyncPolicy,
+    ) -> Result<(Self, SyncOutcome), SyncError> {
+        if !data_dir.join("eurofxref-hist.db3").exists() {
+            if policy.offline {
+                return Err(SyncError::NoEcbHistory);
+            }
+
+            return ExchangeStore::sync(data_dir, options)
+                .map(|(store, report)| (store, SyncOutcome::Synced(report)));
+        }
+
+        let store = ExchangeStore::open(data_dir.clone()).map_err(|_| SyncError::CouldNotRead)?;
+        let now = Utc::now();
+
+        let is_stale =
+            policy
+                .max_business_days_behind
+                .is_some_and(|max_days| match store.get_latest_date() {
+                    Some(latest) => business_days_between(latest, now.date_naive()) > max_days,
+                    None => true,
+                });
+        if !is_stale {
+            return Ok((store, SyncOutcome::UpToDate));
+        }
+        if policy.offline {
+            return Ok((store, SyncOutcome::Offline));
+        }
+
+        let time = |key| persistence::metadata::get_time(&store.conn, key);
+        let last_attempt_at = time(persistence::metadata::LAST_SYNC_ATTEMPT_AT)
+            .and_then(|attempt| Ok(attempt.max(time(persistence::metadata::LAST_SYNCED_AT)?)))
+            .map_err(|_| SyncError::CouldNotRead)?;
+        // A clock that went backwards counts as too recent
+        let is_throttled = last_attempt_at.is_some_and(|attempt| {
+            (now - attempt)
+                .to_std()
+                .map_or(true, |elapsed| elapsed < policy.min_attempt_interval)
+        });
+        if is_throttled {
+            return Ok((store, SyncOutcome::Throttled));
+        }
+
+        persistence::metadata::set(
+            &store.conn,
+            persistence::metadata::LAST_SYNC_ATTEMPT_AT,
+            &now.to_rfc3339(),
+        )
+        .map_err(|_| SyncError::CouldNotRead)?;
+
+        match ExchangeStore::sync(data_dir, options) {
+            Ok((store, report)) => Ok((store, SyncOutcome::Synced(report))),
+            Err(err) => Ok((store, SyncOutcome::Failed(err))),
+        }
     }
 
     /// Creates a new instance based on the existing data store. If you need
@@ -147,11 +482,7 @@ impl ExchangeStore {
                     }
                     rusqlite::Error::SqlInputError { msg, .. } => {
                         // I uhh.. I think this is fine?
-                        let currency = msg
-                            .split(": ")
-                            .nth(1)
-                            .and_then(iso::find)
-                            .unwrap();
+                        let currency = msg.split(": ").nth(1).and_then(iso::find).unwrap();
                         ConversionError::InvalidCurrency(*currency)
                     }
                     _ => ConversionError::MalformedExchangeStore,
@@ -215,9 +546,503 @@ impl ExchangeStore {
         self.convert(from_amount, to_currency, on_date, find_rates)
     }
 
+    /// Converts currencies using the rate on the given date, the way
+    /// `options` says. e.g with `ConversionOptions::fallback` it behaves like
+    /// `convert_on_date_with_fallback`. The converted amount is rounded in
+    /// the target currency.
+    pub fn convert_on_date_with_options<'c>(
+        &self,
+        from_amount: Money<'c, Currency>,
+        to_currency: &'c Currency,
+        on_date: NaiveDate,
+        options: &ConversionOptions,
+    ) -> Result<Money<'c, Currency>, ConversionError> {
+        let converted = match options.fallback {
+            true => self.convert_on_date_with_fallback(from_amount, to_currency, on_date),
+            false => self.convert_on_date(from_amount, to_currency, on_date),
+        }?;
+
+        Ok(options.rounding.round(converted))
+    }
+
+    /// Quotes a conversion the way a customer would get it. The fees of
+    /// `pricing` are taken from the amount first, and what's left is
+    /// converted at the mid rate less the spread of the pair. The conversion
+    /// at the mid rate is kept alongside, e.g to show the markup.
+    ///
+    /// The amounts, and fees are rounded with `options.rounding`, while the
+    /// rates are always exact.
+    pub fn quote<'c>(
+        &self,
+        from_amount: Money<'c, Currency>,
+        to_currency: &'c Currency,
+        on_date: NaiveDate,
+        options: &ConversionOptions,
+        pricing: &Pricing,
+    ) -> Result<Quote<'c>, ConversionError> {
+        let from_currency = from_amount.currency();
+        let exact = ConversionOptions {
+            rounding: Rounding::default(),
+            ..*options
+        };
+        let mid_rate = *self
+            .convert_on_date_with_options(
+                Money::from_decimal(Decimal::ONE, from_currency),
+                to_currency,
+                on_date,
+                &exact,
+            )?
+            .amount();
+        let mid_amount =
+            self.convert_on_date_with_options(from_amount.clone(), to_currency, on_date, options)?;
+
+        let fees = options.rounding.round(Money::from_decimal(
+            pricing.fees_of(*from_amount.amount()),
+            from_currency,
+        ));
+        let remaining = *from_amount.amount() - *fees.amount();
+        if remaining.is_sign_negative() && !remaining.is_zero() {
+            return Err(ConversionError::FeesExceedAmount);
+        }
+
+        let spread = pricing.spread_of(from_currency, to_currency);
+        let remaining_at_mid = self.convert_on_date_with_options(
+            Money::from_decimal(remaining, from_currency),
+            to_currency,
+            on_date,
+            &exact,
+        )?;
+        let customer_amount = options.rounding.round(Money::from_decimal(
+            spread.apply(*remaining_at_mid.amount()),
+            to_currency,
+        ));
+
+        Ok(Quote {
+            date: on_date,
+            mid_rate,
+            effective_rate: spread.apply(mid_rate),
+            spread,
+            mid_amount,
+            fees,
+            customer_amount,
+        })
+    }
+
+    /// Like `convert`, but converts to several currencies with a single
+    /// lookup of the rates. Failing to convert to one currency doesn't fail
+    /// the others, so each gets its own result, keyed by ISO alpha code.
+    fn convert_many<'c, F>(
+        &self,
+        from_amount: Money<'c, Currency>,
+        to_currencies: &[&'c Currency],
+        on_date: NaiveDate,
+        find_rates: F,
+    ) -> Result<ConversionResults<'c>, ConversionError>
+    where
+        F: FnOnce(Vec<&'c Currency>) -> Result<Vec<ExchangeRate<'c, Currency>>, rusqlite::Error>,
+    {
+        let from_currency = from_amount.currency();
+        let columns = persistence::exchange_rate::currency_columns(&self.conn)
+            .map_err(|_| ConversionError::MalformedExchangeStore)?;
+        let is_recorded = |currency: &Currency| {
+            currency == iso::EUR || columns.iter().any(|c| c == currency.iso_alpha_code)
+        };
+
+        if !is_recorded(from_currency) {
+            return Err(ConversionError::InvalidCurrency(*from_currency));
+        }
+
+        // EUR has no column, and unknown currencies are left out so they
+        // don't fail the query
+        let mut currencies: Vec<&'c Currency> = Vec::new();
+        for currency in [from_currency].iter().chain(to_currencies) {
+            if *currency != iso::EUR && is_recorded(currency) && !currencies.contains(currency) {
+                currencies.push(currency);
+            }
+        }
+
+        // Nothing to look up if it's all EUR
+        let rates = match currencies.is_empty() {
+            true => Vec::new(),
+            false => find_rates(currencies).map_err(|err| match err {
+                rusqlite::Error::QueryReturnedNoRows => ConversionError::NoExchangeRate(on_date),
+                _ => ConversionError::MalformedExchangeStore,
+            })?,
+        };
+        let exchange = rates_to_exchange(rates.as_slice());
+
+        // Every conversion goes through EUR
+        let eur = match from_currency {
+            iso::EUR => Ok(from_amount.clone()),
+            from => exchange
+                .get_rate(from, iso::EUR)
+                .ok_or(ConversionError::NoExchangeRate(on_date))
+                .and_then(|rate| {
+                    rate.convert(from_amount.clone())
+                        .map_err(|_| ConversionError::SameCurrency)
+                }),
+        };
+
+        let results = to_currencies
+            .iter()
+            .map(|to| {
+                let result = match *to {
+                    to if to == from_currency => Err(ConversionError::SameCurrency),
+                    to if !is_recorded(to) => Err(ConversionError::InvalidCurrency(*to)),
+                    iso::EUR => eur
+                        .clone()
+                        .map(|eur| Money::from_decimal(*eur.amount(), iso::EUR)),
+                    to => eur.clone().and_then(|eur| {
+                        exchange
+                            .get_rate(iso::EUR, to)
+                            .ok_or(ConversionError::NoExchangeRate(on_date))?
+                            .convert(eur)
+                            .map(|money| Money::from_decimal(*money.amount(), to))
+                            .map_err(|_| ConversionError::SameCurrency)
+                    }),
+                };
+
+                (to.iso_alpha_code, result)
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Converts an amount to several currencies on the given date, looking up
+    /// the rates of all of them at once. Fails as a whole if there's no
+    /// record on that date, or if the source currency isn't recorded.
+    /// Otherwise each target gets its own result, keyed by ISO alpha code.
+    pub fn convert_to_many<'c>(
+        &self,
+        from_amount: Money<'c, Currency>,
+        to_currencies: &[&'c Currency],
+        on_date: NaiveDate,
+    ) -> Result<ConversionResults<'c>, ConversionError> {
+        let find_rates = |currencies: Vec<&'c Currency>| {
+            persistence::exchange_rate::find_rates(&self.conn, currencies.as_slice(), on_date)
+        };
+
+        self.convert_many(from_amount, to_currencies, on_date, find_rates)
+    }
+
+    /// Like `convert_to_many`, but uses interpolated rates if the date isn't
+    /// on record. See `convert_on_date_with_fallback`.
+    pub fn convert_to_many_with_fallback<'c>(
+        &self,
+        from_amount: Money<'c, Currency>,
+        to_currencies: &[&'c Currency],
+        on_date: NaiveDate,
+    ) -> Result<ConversionResults<'c>, ConversionError> {
+        let find_rates = |currencies: Vec<&'c Currency>| {
+            persistence::exchange_rate::find_rates_with_fallback(
+                &self.conn,
+                currencies.as_slice(),
+                on_date,
+            )
+        };
+
+        self.convert_many(from_amount, to_currencies, on_date, find_rates)
+    }
+
+    /// Lists the rate of one unit of `base` in every currency recorded on the
+    /// given date, including interpolated, and missing ones. Rates are
+    /// triangulated through EUR if `base` is not EUR.
+    pub fn rates_on_date(
+        &self,
+        base: &Currency,
+        on_date: NaiveDate,
+    ) -> Result<RatesOnDate, ConversionError> {
+        let (interpolated, rates) = self.all_rates_on(on_date)?;
+
+        // Rate of one EUR in the base currency
+        let base_rate = match base {
+            iso::EUR => Decimal::ONE,
+            _ => rates
+                .iter()
+                .find(|(currency, _)| currency == base.iso_alpha_code)
+                .ok_or(ConversionError::InvalidCurrency(*base))?
+                .1
+                .ok_or(ConversionError::NoExchangeRate(on_date))?,
+        };
+
+        let status = if interpolated {
+            RateStatus::Interpolated
+        } else {
+            RateStatus::Observed
+        };
+
+        let mut quoted: Vec<QuotedRate> = rates
+            .into_iter()
+            .chain([(iso::EUR.iso_alpha_code.to_string(), Some(Decimal::ONE))])
+            .filter(|(currency, _)| currency != base.iso_alpha_code)
+            .map(|(currency, rate)| QuotedRate {
+                currency,
+                rate: rate.map(|rate| rate / base_rate),
+                status: rate.map_or(RateStatus::Missing, |_| status),
+            })
+            .collect();
+        quoted.sort_by(|a, b| a.currency.cmp(&b.currency));
+
+        Ok(RatesOnDate {
+            date: on_date,
+            base: base.iso_alpha_code,
+            rates: quoted,
+        })
+    }
+
+    /// The rate of every one of `currencies` in every other on the given
+    /// date, triangulated through EUR from the rates of that one date. Fails
+    /// if there's no record on that date, or if any of the currencies isn't
+    /// recorded by ECB.
+    pub fn cross_rates(
+        &self,
+        on_date: NaiveDate,
+        currencies: &[&Currency],
+    ) -> Result<CrossRates, ConversionError> {
+        let (interpolated, rates) = self.all_rates_on(on_date)?;
+
+        // Rate of one EUR in each of the currencies
+        let in_eur = currencies
+            .iter()
+            .map(|currency| match *currency {
+                iso::EUR => Ok(Some(Decimal::ONE)),
+                _ => rates
+                    .iter()
+                    .find(|(code, _)| code == currency.iso_alpha_code)
+                    .map(|(_, rate)| *rate)
+                    .ok_or(ConversionError::InvalidCurrency(**currency)),
+            })
+            .collect::<Result<Vec<Option<Decimal>>, ConversionError>>()?;
+
+        let matrix = in_eur
+            .iter()
+            .map(|from| {
+                in_eur
+                    .iter()
+                    .map(|to| match (from, to) {
+                        (Some(from), Some(to)) => Some(to / from),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(CrossRates {
+            date: on_date,
+            currencies: currencies
+                .iter()
+                .map(|currency| currency.iso_alpha_code)
+                .collect(),
+            rates: matrix,
+            status: if interpolated {
+                RateStatus::Interpolated
+            } else {
+                RateStatus::Observed
+            },
+        })
+    }
+
+    /// Every rate of one EUR recorded on the given date, and whether they
+    /// were interpolated
+    fn all_rates_on(&self, on_date: NaiveDate) -> Result<AllRates, ConversionError> {
+        let (interpolated, rates) = persistence::exchange_rate::find_all_rates(&self.conn, on_date)
+            .map_err(|err| match err {
+                rusqlite::Error::QueryReturnedNoRows => ConversionError::NoExchangeRate(on_date),
+                _ => ConversionError::MalformedExchangeStore,
+            })?;
+
+        let rates = rates
+            .into_iter()
+            .map(|(currency, rate)| {
+                rate.map(|rate| Decimal::from_str_exact(rate.as_ref()))
+                    .transpose()
+                    .map(|rate| (currency, rate))
+                    .map_err(|_| ConversionError::MalformedExchangeStore)
+            })
+            .collect::<Result<Vec<_>, ConversionError>>()?;
+
+        Ok((interpolated, rates))
+    }
+
+    /// Lists the rate of one unit of `from` in `to` on every date from
+    /// `start` to `end`, including both, ordered by date. Rates are
+    /// triangulated through EUR if neither currency is EUR.
+    ///
+    /// Interpolated rates are included, and marked as such, so there's a
+    /// rate on every calendar day between the first, and last published
+    /// ones. Dates where either currency has no rate are left out.
+    pub fn rates_between(
+        &self,
+        from: &Currency,
+        to: &Currency,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<DatedRate>, ConversionError> {
+        if from == to {
+            return Err(ConversionError::SameCurrency);
+        }
+
+        let columns = persistence::exchange_rate::currency_columns(&self.conn)
+            .map_err(|_| ConversionError::MalformedExchangeStore)?;
+        let currencies: Vec<&Currency> = [from, to]
+            .into_iter()
+            .filter(|currency| *currency != iso::EUR)
+            .collect();
+        if let Some(currency) = currencies.iter().find(|currency| {
+            !columns
+                .iter()
+                .any(|column| column == currency.iso_alpha_code)
+        }) {
+            return Err(ConversionError::InvalidCurrency(**currency));
+        }
+
+        let rows =
+            persistence::exchange_rate::find_rates_between(&self.conn, &currencies, start, end)
+                .map_err(|_| ConversionError::MalformedExchangeStore)?;
+
+        // Rate of one EUR in the currency, if there's one
+        let eur_rate = |currency: &Currency, rates: &[Option<String>]| match currency {
+            iso::EUR => Ok(Some(Decimal::ONE)),
+            _ => rates[currencies
+                .iter()
+                .position(|c| *c == currency)
+                .unwrap_or_default()]
+            .as_deref()
+            .map(Decimal::from_str_exact)
+            .transpose()
+            .map_err(|_| ConversionError::MalformedExchangeStore),
+        };
+
+        rows.into_iter()
+            .filter_map(|(date, interpolated, rates)| {
+                let rates = eur_rate(from, &rates).and_then(|from_rate| {
+                    eur_rate(to, &rates).map(|to_rate| from_rate.zip(to_rate))
+                });
+
+                match rates {
+                    Ok(Some((from_rate, to_rate))) => Some(Ok(DatedRate {
+                        date,
+                        rate: to_rate / from_rate,
+                        status: match interpolated {
+                            true => RateStatus::Interpolated,
+                            false => RateStatus::Observed,
+                        },
+                    })),
+                    Ok(None) => None,
+                    Err(err) => Some(Err(err)),
+                }
+            })
+            .collect()
+    }
+
+    /// Averages the rate of one unit of `from` in `to` over a period, e.g for
+    /// translating a monthly P&L. `method` decides whether only the days the
+    /// ECB published rates on count, or every calendar day.
+    pub fn average_rate(
+        &self,
+        from: &Currency,
+        to: &Currency,
+        period: Period,
+        method: AverageMethod,
+    ) -> Result<AverageRate, ConversionError> {
+        let (start, end) = period.bounds()?;
+        let series = self.rates_between(from, to, start, end)?;
+
+        period::average(period, &series, method).ok_or(ConversionError::NoExchangeRates(start, end))
+    }
+
+    /// The rate of one unit of `from` in `to` on the last date the ECB
+    /// published rates on within a period, e.g for translating a balance
+    /// sheet. Interpolated rates are never used, so the date may be a few
+    /// days before the period ends.
+    pub fn closing_rate(
+        &self,
+        from: &Currency,
+        to: &Currency,
+        period: Period,
+    ) -> Result<ClosingRate, ConversionError> {
+        let (start, end) = period.bounds()?;
+        let series = self.rates_between(from, to, start, end)?;
+
+        period::closing(period, &series).ok_or(ConversionError::NoExchangeRates(start, end))
+    }
+
+    /// Describes the rate of one unit of `from` in `to` from `start` to `end`,
+    /// including both, e.g its range, and volatility. Only the rates the ECB
+    /// published count. See `RateStats`.
+    pub fn rate_stats(
+        &self,
+        from: &Currency,
+        to: &Currency,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<RateStats, ConversionError> {
+        let series = self.rates_between(from, to, start, end)?;
+
+        analytics::stats(start, end, &series).ok_or(ConversionError::NoExchangeRates(start, end))
+    }
+
+    /// Summarizes what's in the local data store, like its date range, which
+    /// currencies it covers, and when it was last synced.
+    pub fn stats(&self) -> Result<StoreStats, InitError> {
+        let db_size = std::fs::metadata(self.data_dir.join("eurofxref-hist.db3"))
+            .map_err(|_| InitError::CouldNotRead)?
+            .len();
+        let (observed_rows, interpolated_rows) =
+            persistence::stats::count_rows(&self.conn).map_err(|_| InitError::CouldNotRead)?;
+        let date_range = persistence::stats::observed_date_range(&self.conn)
+            .map_err(|_| InitError::CouldNotRead)?;
+
+        let mut currencies: Vec<CurrencyCoverage> =
+            persistence::stats::currency_date_ranges(&self.conn)
+                .map_err(|_| InitError::CouldNotRead)?
+                .into_iter()
+                .map(|(currency, first_date, last_date)| CurrencyCoverage {
+                    currency,
+                    first_date,
+                    last_date,
+                })
+                .collect();
+        currencies.sort_by(|a, b| a.currency.cmp(&b.currency));
+
+        let last_synced_at =
+            persistence::metadata::get_time(&self.conn, persistence::metadata::LAST_SYNCED_AT)
+                .map_err(|_| InitError::CouldNotRead)?;
+
+        Ok(StoreStats {
+            data_dir: self.data_dir.clone(),
+            db_size,
+            first_date: date_range.map(|(first, _)| first),
+            latest_date: date_range.map(|(_, latest)| latest),
+            observed_rows,
+            interpolated_rows,
+            currencies,
+            last_synced_at,
+            source_url: persistence::metadata::get(&self.conn, persistence::metadata::SOURCE_URL)
+                .map_err(|_| InitError::CouldNotRead)?,
+        })
+    }
+
     pub fn get_latest_date(&self) -> Option<NaiveDate> {
         persistence::exchange_rate::get_latest_date(&self.conn).ok()
     }
+
+    /// Directory of the local data store
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+}
+
+/// Counts the weekdays after `from`, up to and including `to`. ECB doesn't
+/// publish rates on weekends, so those never make a store stale.
+#[cfg(any(feature = "download", test))]
+fn business_days_between(from: NaiveDate, to: NaiveDate) -> u32 {
+    from.iter_days()
+        .skip(1)
+        .take_while(|date| *date <= to)
+        .filter(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .count() as u32
 }
 
 /// Creates an `Exchange`, and sets it with all the given rates.
@@ -230,30 +1055,557 @@ fn rates_to_exchange<'c>(rates: &'c [ExchangeRate<'c, Currency>]) -> Exchange<'c
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::path::PathBuf;
 
     use chrono::NaiveDate;
-    use rand::distributions::{Alphanumeric, DistString};
     use rust_decimal::Decimal;
     use rusty_money::{iso, Money};
 
-    use crate::exchange_store::{ConversionError, ExchangeStore};
+    #[cfg(feature = "download")]
+    use crate::{ecb::SyncOptions, exchange_store::SyncError};
+    use crate::{
+        exchange_store::{ConversionError, CurrencyCoverage, ExchangeStore, RateStatus},
+        test_support::{date, fixture_store},
+    };
 
     #[test]
+    #[cfg(feature = "download")]
     fn it_syncs_with_ecb() {
+        use rand::distributions::{Alphanumeric, DistString};
+
         let rand_str = Alphanumeric.sample_string(&mut rand::thread_rng(), 16);
-        let data_dir = PathBuf::new()
-            .join("/tmp")
-            .join(format!("moneyman_{}", rand_str));
+        let data_dir = std::env::temp_dir().join(format!("moneyman_{}", rand_str));
 
         std::fs::create_dir(&data_dir).expect("failed to create test directory");
 
-        ExchangeStore::sync(data_dir.clone()).unwrap();
+        ExchangeStore::sync(data_dir.clone(), &SyncOptions::default()).unwrap();
 
         assert!(data_dir.join("eurofxref-hist.db3").exists());
     }
 
+    #[test]
+    fn it_counts_business_days() {
+        use crate::exchange_store::business_days_between;
+
+        let friday = date("2023-05-12");
+
+        // Friday to the following Monday
+        assert_eq!(1, business_days_between(friday, date("2023-05-15")));
+        assert_eq!(0, business_days_between(friday, date("2023-05-14")));
+        assert_eq!(0, business_days_between(friday, friday));
+        assert_eq!(0, business_days_between(friday, date("2023-05-11")));
+        assert_eq!(5, business_days_between(date("2023-05-05"), friday));
+    }
+
+    #[test]
+    #[cfg(feature = "download")]
+    fn it_syncs_stale_stores_with_policy() {
+        use crate::{
+            ecb::tests::serve,
+            exchange_store::{SyncOutcome, SyncPolicy},
+            test_support::temp_data_dir,
+        };
+
+        let store = fixture_store();
+        let data_dir = store.data_dir().to_path_buf();
+        let failing = SyncOptions {
+            base_url: serve(vec![(500, Vec::new())]),
+            retries: 0,
+            ..SyncOptions::default()
+        };
+        let stale = SyncPolicy {
+            max_business_days_behind: Some(1),
+            ..SyncPolicy::default()
+        };
+
+        let (_, outcome) =
+            ExchangeStore::open_with_policy(data_dir.clone(), &failing, &SyncPolicy::default())
+                .unwrap();
+        assert!(matches!(outcome, SyncOutcome::UpToDate));
+
+        let offline = SyncPolicy {
+            offline: true,
+            ..stale.clone()
+        };
+        let (_, outcome) =
+            ExchangeStore::open_with_policy(data_dir.clone(), &failing, &offline).unwrap();
+        assert!(matches!(outcome, SyncOutcome::Offline));
+
+        // The fixture ends on 2023-05-12, so it's always stale
+        let (store, outcome) =
+            ExchangeStore::open_with_policy(data_dir.clone(), &failing, &stale).unwrap();
+        assert!(matches!(outcome, SyncOutcome::Failed(_)));
+        assert_eq!(Some(date("2023-05-12")), store.get_latest_date());
+
+        let (_, outcome) =
+            ExchangeStore::open_with_policy(data_dir.clone(), &failing, &stale).unwrap();
+        assert!(matches!(outcome, SyncOutcome::Throttled));
+
+        let missing = temp_data_dir();
+        assert!(matches!(
+            ExchangeStore::open_with_policy(missing, &failing, &offline),
+            Err(SyncError::NoEcbHistory)
+        ));
+    }
+
+    #[test]
+    fn it_converts_with_options() {
+        use crate::{
+            exchange_store::ConversionOptions,
+            rounding::{Rounding, RoundingMode},
+        };
+
+        let store = fixture_store();
+        let saturday = date("2023-05-06");
+        let amount = Money::from_decimal(Decimal::from(1000), iso::EUR);
+        let options = ConversionOptions {
+            fallback: true,
+            rounding: Rounding::minor_units(RoundingMode::HalfEven),
+        };
+
+        let exact = store
+            .convert_on_date_with_fallback(amount.clone(), iso::JPY, saturday)
+            .unwrap();
+        let rounded = store
+            .convert_on_date_with_options(amount.clone(), iso::JPY, saturday, &options)
+            .unwrap();
+
+        assert_eq!(exact.amount().round(), *rounded.amount());
+        assert_eq!(0, rounded.amount().scale());
+        assert!(matches!(
+            store.convert_on_date_with_options(
+                amount,
+                iso::JPY,
+                saturday,
+                &ConversionOptions::default()
+            ),
+            Err(ConversionError::NoExchangeRate(_))
+        ));
+    }
+
+    #[test]
+    fn it_quotes_with_spreads_and_fees() {
+        use crate::{
+            exchange_store::ConversionOptions,
+            pricing::{Fee, Pricing, Spread},
+            rounding::{Rounding, RoundingMode},
+        };
+
+        let store = fixture_store();
+        let thursday = date("2023-05-04");
+        let amount = Money::from_decimal(Decimal::from(1000), iso::EUR);
+        let pricing = Pricing {
+            pair_spreads: vec![(("EUR", "USD"), Spread::percent(Decimal::ONE))],
+            fees: vec![Fee::Fixed(Decimal::from(10)), Fee::Percent(Decimal::ONE)],
+            ..Pricing::default()
+        };
+
+        let quote = store
+            .quote(
+                amount.clone(),
+                iso::USD,
+                thursday,
+                &ConversionOptions::default(),
+                &pricing,
+            )
+            .unwrap();
+
+        // 1 EUR is 1.1074 USD on 2023-05-04
+        let mid_rate = Decimal::from_i128_with_scale(11074, 4);
+        assert_eq!(mid_rate, quote.mid_rate);
+        assert_eq!(
+            mid_rate * Decimal::from_i128_with_scale(99, 2),
+            quote.effective_rate
+        );
+        assert_eq!(
+            Money::from_decimal(Decimal::from(1000) * mid_rate, iso::USD),
+            quote.mid_amount
+        );
+        assert_eq!(Money::from_decimal(Decimal::from(20), iso::EUR), quote.fees);
+        assert_eq!(
+            Money::from_decimal(Decimal::from(980) * quote.effective_rate, iso::USD),
+            quote.customer_amount
+        );
+
+        let rounded = ConversionOptions {
+            rounding: Rounding::minor_units(RoundingMode::HalfEven),
+            ..ConversionOptions::default()
+        };
+        let quote = store
+            .quote(amount, iso::USD, thursday, &rounded, &Pricing::default())
+            .unwrap();
+        assert_eq!(quote.mid_amount, quote.customer_amount);
+        assert_eq!(2, quote.customer_amount.amount().scale());
+
+        assert!(matches!(
+            store.quote(
+                Money::from_decimal(Decimal::from(5), iso::EUR),
+                iso::USD,
+                thursday,
+                &ConversionOptions::default(),
+                &pricing
+            ),
+            Err(ConversionError::FeesExceedAmount)
+        ));
+    }
+
+    #[test]
+    fn it_lists_rates_between_dates() {
+        let store = fixture_store();
+        let rates = store
+            .rates_between(iso::USD, iso::JPY, date("2023-05-05"), date("2023-05-08"))
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                RateStatus::Observed,
+                RateStatus::Interpolated,
+                RateStatus::Interpolated,
+                RateStatus::Observed
+            ],
+            rates.iter().map(|rate| rate.status).collect::<Vec<_>>()
+        );
+        // 147.8 JPY / 1.1014 USD on 2023-05-05
+        assert_eq!(
+            Decimal::from_i128_with_scale(1478, 1) / Decimal::from_i128_with_scale(11014, 4),
+            rates[0].rate
+        );
+
+        let to_eur = store
+            .rates_between(iso::USD, iso::EUR, date("2023-05-12"), date("2023-06-30"))
+            .unwrap();
+        assert_eq!(1, to_eur.len());
+        assert_eq!(
+            Decimal::ONE / Decimal::from_i128_with_scale(10892, 4),
+            to_eur[0].rate
+        );
+
+        assert!(matches!(
+            store.rates_between(iso::USD, iso::AED, NaiveDate::MIN, NaiveDate::MAX),
+            Err(ConversionError::InvalidCurrency(_))
+        ));
+    }
+
+    #[test]
+    fn it_averages_rates_over_a_period() {
+        use crate::period::{AverageMethod, Period};
+
+        let store = fixture_store();
+        let period = Period::Month {
+            year: 2023,
+            month: 5,
+        };
+
+        let published = store
+            .average_rate(iso::EUR, iso::USD, period, AverageMethod::PublishedDays)
+            .unwrap();
+        // 2023-05-02 to 2023-05-12
+        let usd = [
+            10965, 11043, 11074, 11014, 11037, 10959, 10950, 10930, 10892,
+        ];
+        assert_eq!(
+            usd.iter()
+                .map(|rate| Decimal::from_i128_with_scale(*rate, 4))
+                .sum::<Decimal>()
+                / Decimal::from(9),
+            published.rate
+        );
+        assert_eq!((9, 0), (published.days, published.interpolated_days));
+
+        let calendar = store
+            .average_rate(iso::EUR, iso::USD, period, AverageMethod::CalendarDays)
+            .unwrap();
+        // 2023-05-01 is a holiday between 04-28, and 05-02
+        assert_eq!((12, 3), (calendar.days, calendar.interpolated_days));
+
+        assert!(matches!(
+            store.average_rate(
+                iso::EUR,
+                iso::USD,
+                Period::Year(2022),
+                AverageMethod::PublishedDays
+            ),
+            Err(ConversionError::NoExchangeRates(_, _))
+        ));
+        assert!(matches!(
+            store.average_rate(
+                iso::EUR,
+                iso::USD,
+                Period::Quarter {
+                    year: 2023,
+                    quarter: 0
+                },
+                AverageMethod::PublishedDays
+            ),
+            Err(ConversionError::InvalidPeriod(_))
+        ));
+    }
+
+    #[test]
+    fn it_finds_closing_rates() {
+        use crate::period::Period;
+
+        let store = fixture_store();
+        let april = Period::Month {
+            year: 2023,
+            month: 4,
+        };
+
+        // 2023-04-29, and 30 are a weekend
+        let closing = store.closing_rate(iso::EUR, iso::USD, april).unwrap();
+        assert_eq!(date("2023-04-28"), closing.date);
+        assert_eq!(Decimal::from_i128_with_scale(10981, 4), closing.rate);
+
+        // The store ends before the quarter does
+        let closing = store
+            .closing_rate(
+                iso::USD,
+                iso::GBP,
+                Period::Quarter {
+                    year: 2023,
+                    quarter: 2,
+                },
+            )
+            .unwrap();
+        assert_eq!(date("2023-05-12"), closing.date);
+
+        assert!(matches!(
+            store.closing_rate(iso::EUR, iso::USD, Period::Year(2022)),
+            Err(ConversionError::NoExchangeRates(_, _))
+        ));
+    }
+
+    #[test]
+    fn it_describes_rates_between_dates() {
+        let store = fixture_store();
+
+        let stats = store
+            .rate_stats(iso::EUR, iso::USD, date("2023-05-01"), date("2023-05-31"))
+            .unwrap();
+
+        assert_eq!(9, stats.observations);
+        assert_eq!(date("2023-05-02"), stats.first.date);
+        assert_eq!(date("2023-05-12"), stats.last.date);
+        assert_eq!(
+            (date("2023-05-12"), Decimal::from_i128_with_scale(10892, 4)),
+            (stats.min.date, stats.min.rate)
+        );
+        assert_eq!(
+            (date("2023-05-04"), Decimal::from_i128_with_scale(11074, 4)),
+            (stats.max.date, stats.max.rate)
+        );
+        // 1.0965 to 1.0892
+        assert_eq!(
+            (Decimal::from_i128_with_scale(-73, 4) / Decimal::from_i128_with_scale(10965, 4))
+                * Decimal::ONE_HUNDRED,
+            stats.change_percent
+        );
+        assert!(stats.volatility.is_some());
+
+        assert!(matches!(
+            store.rate_stats(iso::EUR, iso::USD, date("2023-05-13"), date("2023-05-31")),
+            Err(ConversionError::NoExchangeRates(_, _))
+        ));
+    }
+
+    #[test]
+    fn it_builds_cross_rate_matrices() {
+        let store = fixture_store();
+        let thursday = date("2023-05-04");
+        let usd = Decimal::from_i128_with_scale(11074, 4);
+        let jpy = Decimal::from_i128_with_scale(14892, 2);
+
+        let cross = store
+            .cross_rates(thursday, &[iso::USD, iso::EUR, iso::JPY])
+            .unwrap();
+        assert_eq!(vec!["USD", "EUR", "JPY"], cross.currencies);
+        assert_eq!(RateStatus::Observed, cross.status);
+        assert_eq!(
+            vec![
+                vec![
+                    Some(Decimal::ONE),
+                    Some(Decimal::ONE / usd),
+                    Some(jpy / usd)
+                ],
+                vec![Some(usd), Some(Decimal::ONE), Some(jpy)],
+                vec![
+                    Some(usd / jpy),
+                    Some(Decimal::ONE / jpy),
+                    Some(Decimal::ONE)
+                ],
+            ],
+            cross.rates
+        );
+
+        assert!(matches!(
+            store.cross_rates(thursday, &[iso::USD, iso::AED]),
+            Err(ConversionError::InvalidCurrency(currency)) if currency == *iso::AED
+        ));
+        assert!(matches!(
+            store.cross_rates(date("2000-01-01"), &[iso::USD]),
+            Err(ConversionError::NoExchangeRate(_))
+        ));
+    }
+
+    #[test]
+    fn it_lists_rates_on_a_date_against_any_base() {
+        let store = fixture_store();
+        let thursday = date("2023-05-04");
+
+        let in_eur = store.rates_on_date(iso::EUR, thursday).unwrap();
+        let usd = in_eur
+            .rates
+            .iter()
+            .find(|rate| rate.currency == "USD")
+            .unwrap();
+        assert_eq!(Some(Decimal::from_i128_with_scale(11074, 4)), usd.rate);
+        assert_eq!(RateStatus::Observed, usd.status);
+        assert!(in_eur.rates.iter().all(|rate| rate.currency != "EUR"));
+
+        let cyp = in_eur
+            .rates
+            .iter()
+            .find(|rate| rate.currency == "CYP")
+            .unwrap();
+        assert_eq!((None, RateStatus::Missing), (cyp.rate, cyp.status));
+
+        let in_usd = store.rates_on_date(iso::USD, thursday).unwrap();
+        let eur = in_usd
+            .rates
+            .iter()
+            .find(|rate| rate.currency == "EUR")
+            .unwrap();
+        let jpy = in_usd
+            .rates
+            .iter()
+            .find(|rate| rate.currency == "JPY")
+            .unwrap();
+        assert_eq!(
+            Some(Decimal::ONE / Decimal::from_i128_with_scale(11074, 4)),
+            eur.rate
+        );
+        assert_eq!(
+            Some(Decimal::from_i128_with_scale(14892, 2) / Decimal::from_i128_with_scale(11074, 4)),
+            jpy.rate
+        );
+
+        let saturday = date("2023-05-06");
+        let in_gbp = store.rates_on_date(iso::GBP, saturday).unwrap();
+        assert!(in_gbp
+            .rates
+            .iter()
+            .filter(|rate| rate.rate.is_some())
+            .all(|rate| rate.status == RateStatus::Interpolated));
+        assert!(matches!(
+            store.rates_on_date(iso::EUR, date("2023-06-01")),
+            Err(ConversionError::NoExchangeRate(_))
+        ));
+        assert!(matches!(
+            store.rates_on_date(iso::ARS, saturday),
+            Err(ConversionError::InvalidCurrency(_))
+        ));
+    }
+
+    #[test]
+    fn it_converts_to_many_currencies_at_once() {
+        let store = fixture_store();
+        let thursday = date("2023-05-04");
+        let usd = Money::from_decimal(Decimal::from(100), iso::USD);
+
+        let results = store
+            .convert_to_many(
+                usd.clone(),
+                &[iso::JPY, iso::EUR, iso::USD, iso::ARS, iso::HRK],
+                thursday,
+            )
+            .unwrap();
+
+        for currency in [iso::JPY, iso::EUR] {
+            assert_eq!(
+                store
+                    .convert_on_date(usd.clone(), currency, thursday)
+                    .unwrap(),
+                *results[currency.iso_alpha_code].as_ref().unwrap()
+            );
+        }
+        assert!(matches!(results["USD"], Err(ConversionError::SameCurrency)));
+        assert!(matches!(
+            results["ARS"],
+            Err(ConversionError::InvalidCurrency(_))
+        ));
+        // No longer recorded by ECB in 2023
+        assert!(matches!(
+            results["HRK"],
+            Err(ConversionError::NoExchangeRate(_))
+        ));
+
+        let saturday = date("2023-05-06");
+        let eur = Money::from_decimal(Decimal::from(100), iso::EUR);
+        assert!(matches!(
+            store.convert_to_many(eur.clone(), &[iso::USD], saturday),
+            Err(ConversionError::NoExchangeRate(_))
+        ));
+        assert_eq!(
+            store
+                .convert_on_date_with_fallback(eur.clone(), iso::USD, saturday)
+                .unwrap(),
+            store
+                .convert_to_many_with_fallback(eur, &[iso::USD], saturday)
+                .unwrap()["USD"]
+                .clone()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_summarizes_the_store() {
+        let store = fixture_store();
+
+        let stats = store.stats().unwrap();
+
+        assert_eq!(Some(date("2023-04-27")), stats.first_date);
+        assert_eq!(Some(date("2023-05-12")), stats.latest_date);
+        // 11 weekdays, and the 5 weekend days between them
+        assert_eq!((11, 5), (stats.observed_rows, stats.interpolated_rows));
+        assert!(stats.db_size > 0);
+        assert!(stats.currencies.iter().all(|c| c.currency != "CYP"));
+        assert_eq!(
+            Some(&CurrencyCoverage {
+                currency: "USD".to_string(),
+                first_date: date("2023-04-27"),
+                last_date: date("2023-05-12"),
+            }),
+            stats.currencies.iter().find(|c| c.currency == "USD")
+        );
+        // Seeded without syncing
+        assert_eq!((None, None), (stats.last_synced_at, stats.source_url));
+    }
+
+    #[test]
+    #[cfg(feature = "download")]
+    fn it_records_when_and_where_it_synced_from() {
+        use crate::{
+            ecb::tests::{serve, zip_history},
+            test_support::{fixture_csv, temp_data_dir},
+        };
+
+        let base_url = serve(vec![(200, zip_history(&fixture_csv()))]);
+        let options = SyncOptions {
+            base_url: base_url.clone(),
+            ..SyncOptions::default()
+        };
+
+        let (store, _report) = ExchangeStore::sync(temp_data_dir(), &options).unwrap();
+        let stats = store.stats().unwrap();
+
+        assert_eq!(
+            Some(format!("{base_url}/eurofxref-hist.zip")),
+            stats.source_url
+        );
+        assert!(stats.last_synced_at.is_some());
+    }
+
     #[test]
     /// This should succeed since there's a rate on this date
     fn it_converts_currencies_on_available_dates() {
@@ -267,7 +1619,7 @@ mod tests {
 
         let store = ExchangeStore::open(data_dir).unwrap();
         let amount_in_eur = Money::from_decimal(Decimal::from(1000), iso::EUR);
-        let date = NaiveDate::from_ymd_opt(2023, 05, 04).unwrap();
+        let date = NaiveDate::from_ymd_opt(2023, 5, 4).unwrap();
         let amount_in_usd = store
             .convert_on_date(amount_in_eur, iso::USD, date)
             .unwrap();
@@ -292,7 +1644,7 @@ mod tests {
 
         let store = ExchangeStore::open(data_dir).unwrap();
         let amount_in_eur = Money::from_decimal(Decimal::from(1000), iso::EUR);
-        let date = NaiveDate::from_ymd_opt(2023, 05, 06).unwrap();
+        let date = NaiveDate::from_ymd_opt(2023, 5, 6).unwrap();
 
         match dbg!(store.convert_on_date(amount_in_eur, iso::USD, date)) {
             Ok(_) => panic!("expected to fail"),
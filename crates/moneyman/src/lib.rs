@@ -1,5 +1,21 @@
+pub use crate::analytics::{
+    exponential_moving_average, percent_change, resample, simple_moving_average, Ohlc, RateStats,
+    Resample, PUBLISHED_DAYS_PER_YEAR,
+};
+#[cfg(feature = "download")]
+pub use crate::ecb::SyncOptions;
 pub use crate::exchange_store::*;
+pub use crate::period::{AverageMethod, AverageRate, ClosingRate, InvalidPeriod, Period};
+pub use crate::pricing::{Fee, Pricing, Quote, Spread};
+pub use crate::rounding::{Rounding, RoundingMode};
 
+pub(crate) mod analytics;
+#[cfg(feature = "download")]
 pub(crate) mod ecb;
 pub(crate) mod exchange_store;
+pub(crate) mod period;
 pub(crate) mod persistence;
+pub(crate) mod pricing;
+pub(crate) mod rounding;
+#[cfg(test)]
+pub(crate) mod test_support;
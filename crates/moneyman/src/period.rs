@@ -0,0 +1,393 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::exchange_store::{DatedRate, RateStatus};
+
+/// A span of dates rates are aggregated over, e.g for accounting
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    /// A calendar month, from 1 to 12
+    Month { year: i32, month: u32 },
+    /// A calendar quarter, from 1 to 4
+    Quarter { year: i32, quarter: u32 },
+    /// A calendar year
+    Year(i32),
+    /// Any range of dates, including both ends. e.g a fiscal year.
+    Range { start: NaiveDate, end: NaiveDate },
+}
+
+/// Why a `Period` couldn't be parsed, or doesn't exist, e.g month 13
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("invalid period {0:?}. Expected e.g 2023-05, 2023-Q2, 2023, or 2023-04-01..2023-06-30")]
+pub struct InvalidPeriod(pub String);
+
+/// The first day of a month, if it exists
+fn first_of(year: i32, month: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, 1)
+}
+
+impl Period {
+    /// The first, and last dates of the period. Fails if the period doesn't
+    /// exist, e.g month 13, quarter 0, or a range that ends before it starts.
+    pub fn bounds(&self) -> Result<(NaiveDate, NaiveDate), InvalidPeriod> {
+        let months = |year: i32, first: u32, count: u32| {
+            let start = first_of(year, first)?;
+            let end = start
+                .checked_add_months(chrono::Months::new(count))
+                .and_then(|next| next.pred_opt())?;
+
+            Some((start, end))
+        };
+
+        let bounds = match *self {
+            Period::Month { year, month } => months(year, month, 1),
+            Period::Quarter {
+                year,
+                quarter: quarter @ 1..=4,
+            } => months(year, (quarter - 1) * 3 + 1, 3),
+            Period::Quarter { .. } => None,
+            Period::Year(year) => months(year, 1, 12),
+            Period::Range { start, end } => (start <= end).then_some((start, end)),
+        };
+
+        bounds.ok_or_else(|| InvalidPeriod(self.to_string()))
+    }
+
+    /// Every calendar month the period touches, in order. Months at either
+    /// end are cut to the period.
+    pub fn months(&self) -> Result<Vec<Period>, InvalidPeriod> {
+        self.split(1)
+    }
+
+    /// Every calendar quarter the period touches, in order. Quarters at
+    /// either end are cut to the period.
+    pub fn quarters(&self) -> Result<Vec<Period>, InvalidPeriod> {
+        self.split(3)
+    }
+
+    fn split(&self, months: u32) -> Result<Vec<Period>, InvalidPeriod> {
+        let (start, end) = self.bounds()?;
+        let mut periods = Vec::new();
+        let mut year = start.year();
+        let mut first_month = (start.month0() / months) * months + 1;
+
+        while first_of(year, first_month).is_some_and(|first| first <= end) {
+            let whole = match months {
+                3 => Period::Quarter {
+                    year,
+                    quarter: first_month.div_ceil(3),
+                },
+                _ => Period::Month {
+                    year,
+                    month: first_month,
+                },
+            };
+            let (whole_start, whole_end) = whole.bounds()?;
+
+            periods.push(match (whole_start < start, whole_end > end) {
+                (false, false) => whole,
+                _ => Period::Range {
+                    start: whole_start.max(start),
+                    end: whole_end.min(end),
+                },
+            });
+
+            first_month += months;
+            if first_month > 12 {
+                first_month = 1;
+                year += 1;
+            }
+        }
+
+        Ok(periods)
+    }
+}
+
+/// Writes the period the way `FromStr` reads it, e.g `2023-Q2`
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Month { year, month } => write!(f, "{year}-{month:02}"),
+            Period::Quarter { year, quarter } => write!(f, "{year}-Q{quarter}"),
+            Period::Year(year) => write!(f, "{year}"),
+            Period::Range { start, end } => write!(f, "{start}..{end}"),
+        }
+    }
+}
+
+/// Reads `2023-05`, `2023-Q2`, `2023`, or `2023-04-01..2023-06-30`
+impl FromStr for Period {
+    type Err = InvalidPeriod;
+
+    fn from_str(period: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidPeriod(period.to_string());
+        let trimmed = period.trim();
+
+        if let Some((start, end)) = trimmed.split_once("..") {
+            let date = |date: &str| date.trim().parse::<NaiveDate>().map_err(|_| invalid());
+            let (start, end) = (date(start)?, date(end)?);
+
+            return match start <= end {
+                true => Ok(Period::Range { start, end }),
+                false => Err(invalid()),
+            };
+        }
+
+        let (year, rest) = trimmed.split_once('-').unwrap_or((trimmed, ""));
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        if first_of(year, 1).is_none() {
+            return Err(invalid());
+        }
+
+        match rest.strip_prefix(['Q', 'q']) {
+            _ if rest.is_empty() => Ok(Period::Year(year)),
+            Some(quarter) => match quarter.parse() {
+                Ok(quarter @ 1..=4) => Ok(Period::Quarter { year, quarter }),
+                _ => Err(invalid()),
+            },
+            None => match rest.parse() {
+                Ok(month @ 1..=12) => Ok(Period::Month { year, month }),
+                _ => Err(invalid()),
+            },
+        }
+    }
+}
+
+/// Which days a period average is taken over
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AverageMethod {
+    /// Only days the ECB published rates on
+    #[default]
+    PublishedDays,
+    /// Every calendar day, with the days in between published ones
+    /// interpolated. Weekends, and holidays weigh as much as any other day.
+    CalendarDays,
+}
+
+impl AverageMethod {
+    /// Kebab case name of the method, e.g `published-days`
+    pub fn name(&self) -> &'static str {
+        match self {
+            AverageMethod::PublishedDays => "published-days",
+            AverageMethod::CalendarDays => "calendar-days",
+        }
+    }
+}
+
+/// The average rate of a currency pair over a period
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AverageRate {
+    pub period: Period,
+    /// Units of the target currency per unit of the source currency
+    pub rate: Decimal,
+    pub method: AverageMethod,
+    /// How many days were averaged
+    pub days: usize,
+    /// How many of `days` were interpolated. Always 0 with
+    /// `AverageMethod::PublishedDays`.
+    pub interpolated_days: usize,
+}
+
+/// The rate of a currency pair at the end of a period, e.g for revaluing a
+/// balance sheet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClosingRate {
+    pub period: Period,
+    /// The last date in the period with a published rate. Usually the last
+    /// business day, unless the local data store ends before the period.
+    pub date: NaiveDate,
+    /// Units of the target currency per unit of the source currency
+    pub rate: Decimal,
+}
+
+/// The last published rate of `series`, which is ordered by date. `None` if
+/// there's no published rate.
+pub(crate) fn closing(period: Period, series: &[DatedRate]) -> Option<ClosingRate> {
+    series
+        .iter()
+        .rfind(|rate| rate.status == RateStatus::Observed)
+        .map(|rate| ClosingRate {
+            period,
+            date: rate.date,
+            rate: rate.rate,
+        })
+}
+
+/// Averages the rates of `series` the way `method` says. `None` if there's no
+/// rate to average.
+pub(crate) fn average(
+    period: Period,
+    series: &[DatedRate],
+    method: AverageMethod,
+) -> Option<AverageRate> {
+    let rates: Vec<&DatedRate> = series
+        .iter()
+        .filter(|rate| match method {
+            AverageMethod::PublishedDays => rate.status == RateStatus::Observed,
+            AverageMethod::CalendarDays => true,
+        })
+        .collect();
+
+    if rates.is_empty() {
+        return None;
+    }
+
+    let sum: Decimal = rates.iter().map(|rate| rate.rate).sum();
+
+    Some(AverageRate {
+        period,
+        rate: sum / Decimal::from(rates.len()),
+        method,
+        days: rates.len(),
+        interpolated_days: rates
+            .iter()
+            .filter(|rate| rate.status == RateStatus::Interpolated)
+            .count(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::date;
+
+    fn rate(day: &str, rate: i64, status: RateStatus) -> DatedRate {
+        DatedRate {
+            date: date(day),
+            rate: Decimal::from(rate),
+            status,
+        }
+    }
+
+    #[test]
+    fn it_parses_and_writes_periods() {
+        for period in ["2023-05", "2023-Q2", "2023", "2023-04-06..2024-04-05"] {
+            assert_eq!(period, period.parse::<Period>().unwrap().to_string());
+        }
+
+        assert_eq!(
+            Ok(Period::Month {
+                year: 2023,
+                month: 5
+            }),
+            "2023-5".parse()
+        );
+        assert!("2023-13".parse::<Period>().is_err());
+        assert!("2023-Q5".parse::<Period>().is_err());
+        assert!("2023-05-01..2023-04-01".parse::<Period>().is_err());
+        assert!("may".parse::<Period>().is_err());
+    }
+
+    #[test]
+    fn it_finds_the_bounds_of_periods() {
+        assert_eq!(
+            Ok((date("2024-02-01"), date("2024-02-29"))),
+            Period::Month {
+                year: 2024,
+                month: 2
+            }
+            .bounds()
+        );
+        assert_eq!(
+            Ok((date("2023-10-01"), date("2023-12-31"))),
+            Period::Quarter {
+                year: 2023,
+                quarter: 4
+            }
+            .bounds()
+        );
+        assert_eq!(
+            Ok((date("2023-01-01"), date("2023-12-31"))),
+            Period::Year(2023).bounds()
+        );
+    }
+
+    #[test]
+    fn it_rejects_periods_that_dont_exist() {
+        let month = |month| Period::Month { year: 2023, month };
+        let quarter = |quarter| Period::Quarter {
+            year: 2023,
+            quarter,
+        };
+
+        assert_eq!(Err(InvalidPeriod("2023-13".into())), month(13).bounds());
+        assert!(month(0).bounds().is_err());
+        assert_eq!(Err(InvalidPeriod("2023-Q0".into())), quarter(0).bounds());
+        assert!(quarter(5).quarters().is_err());
+        assert!(Period::Year(i32::MAX).months().is_err());
+        assert!(Period::Range {
+            start: date("2023-05-02"),
+            end: date("2023-05-01"),
+        }
+        .bounds()
+        .is_err());
+    }
+
+    #[test]
+    fn it_splits_periods_into_months_and_quarters() {
+        let fiscal_year: Period = "2023-04-06..2024-04-05".parse().unwrap();
+        let months = fiscal_year.months().unwrap();
+
+        assert_eq!(13, months.len());
+        assert_eq!("2023-04-06..2023-04-30", months[0].to_string());
+        assert_eq!("2023-05", months[1].to_string());
+        assert_eq!("2024-04-01..2024-04-05", months[12].to_string());
+        assert_eq!(
+            vec!["2023-Q1", "2023-Q2", "2023-Q3", "2023-Q4"],
+            Period::Year(2023)
+                .quarters()
+                .unwrap()
+                .iter()
+                .map(Period::to_string)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_averages_published_or_calendar_days() {
+        let series = [
+            rate("2023-05-05", 1, RateStatus::Observed),
+            rate("2023-05-06", 2, RateStatus::Interpolated),
+            rate("2023-05-07", 3, RateStatus::Interpolated),
+            rate("2023-05-08", 6, RateStatus::Observed),
+        ];
+        let period = Period::Year(2023);
+
+        let published = average(period, &series, AverageMethod::PublishedDays).unwrap();
+        assert_eq!(Decimal::from_i128_with_scale(35, 1), published.rate);
+        assert_eq!((2, 0), (published.days, published.interpolated_days));
+
+        let calendar = average(period, &series, AverageMethod::CalendarDays).unwrap();
+        assert_eq!(Decimal::from(3), calendar.rate);
+        assert_eq!((4, 2), (calendar.days, calendar.interpolated_days));
+
+        assert_eq!(None, average(period, &[], AverageMethod::PublishedDays));
+    }
+
+    #[test]
+    fn it_closes_on_the_last_published_rate() {
+        let period = Period::Month {
+            year: 2023,
+            month: 4,
+        };
+        let series = [
+            rate("2023-04-27", 1, RateStatus::Observed),
+            rate("2023-04-28", 2, RateStatus::Observed),
+            rate("2023-04-29", 3, RateStatus::Interpolated),
+            rate("2023-04-30", 4, RateStatus::Interpolated),
+        ];
+
+        assert_eq!(
+            Some(ClosingRate {
+                period,
+                date: date("2023-04-28"),
+                rate: Decimal::TWO,
+            }),
+            closing(period, &series)
+        );
+        assert_eq!(None, closing(period, &series[2..]));
+    }
+}
@@ -1,3 +1,7 @@
 pub(crate) mod exchange_rate;
+#[cfg(any(feature = "download", test))]
 pub(crate) mod fallback;
+pub(crate) mod metadata;
+#[cfg(any(feature = "download", test))]
 pub(crate) mod seed;
+pub(crate) mod stats;
@@ -71,12 +71,95 @@ pub(crate) fn find_rates_with_fallback<'c>(
         .collect();
     let selectable_columns = filtered_currencies.join(", ");
 
-    let mut stmt = conn
-        .prepare(format!("SELECT Date, {selectable_columns} FROM rates WHERE Date = ?1").as_ref())
-        .expect("oh no");
+    conn.prepare(format!("SELECT Date, {selectable_columns} FROM rates WHERE Date = ?1").as_ref())
+        .and_then(|mut stmt| {
+            stmt.query_row([on.to_string()], |row| {
+                row_to_exchange_rates(row, currencies)
+            })
+        })
+}
+
+/// A date, whether its row was interpolated, and the rates of the requested
+/// currencies on it. Missing rates are `None`.
+pub(crate) type DatedRow = (NaiveDate, bool, Vec<Option<String>>);
+
+/// Rates of the given currencies to one EUR on every date from `start` to
+/// `end`, including both, ordered by date. Interpolated rows are included,
+/// and flagged as such. Unlike `find_rates`, EUR must not be given.
+pub(crate) fn find_rates_between(
+    conn: &Connection,
+    currencies: &[&Currency],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<DatedRow>, rusqlite::Error> {
+    let selectable_columns = currencies
+        .iter()
+        .map(|currency| currency.iso_alpha_code)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut stmt = conn.prepare(
+        format!(
+            "
+                SELECT Date, Interpolated, {selectable_columns}
+                    FROM rates
+                    WHERE Date >= ?1
+                        AND Date <= ?2
+                    ORDER BY Date ASC
+                "
+        )
+        .as_ref(),
+    )?;
+
+    let rows = stmt.query_map([start.to_string(), end.to_string()], |row| {
+        let rates = (0..currencies.len())
+            .map(|index| row.get::<usize, Option<String>>(index + 2))
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+        Ok((row.get(0)?, row.get(1)?, rates))
+    })?;
+
+    rows.collect()
+}
+
+/// Columns of `rates` that aren't currencies
+const NON_CURRENCY_COLUMNS: [&str; 4] = ["Date", "Interpolated", "PrevDate", "NextDate"];
+
+/// Every currency column in `rates`, including the ones that no longer exist
+pub(crate) fn currency_columns(conn: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    Ok(conn
+        .prepare_cached("SELECT * FROM rates LIMIT 0")?
+        .column_names()
+        .into_iter()
+        .filter(|name| !NON_CURRENCY_COLUMNS.contains(name))
+        .map(String::from)
+        .collect())
+}
+
+/// Whether a row was interpolated, and its currency columns with their rates
+pub(crate) type RatesRow = (bool, Vec<(String, Option<String>)>);
+
+/// Every currency column on a given date, and whether the row was
+/// interpolated. Missing rates are `None`.
+pub(crate) fn find_all_rates(
+    conn: &Connection,
+    on: NaiveDate,
+) -> Result<RatesRow, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT * FROM rates WHERE Date = ?1")?;
+    let column_names: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
 
     stmt.query_row([on.to_string()], |row| {
-        row_to_exchange_rates(row, currencies)
+        let interpolated = row.get::<&str, bool>("Interpolated")?;
+        let rates = column_names
+            .iter()
+            .filter(|name| !NON_CURRENCY_COLUMNS.contains(&name.as_str()))
+            .map(|name| {
+                row.get::<&str, Option<String>>(name.as_str())
+                    .map(|rate| (name.clone(), rate))
+            })
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+        Ok((interpolated, rates))
     })
 }
 
@@ -117,7 +200,7 @@ pub(crate) fn row_to_exchange_rates<'c>(
 pub(crate) fn parse_rate(
     currency: &Currency,
     rate: String,
-) -> (ExchangeRate<Currency>, ExchangeRate<Currency>) {
+) -> (ExchangeRate<'_, Currency>, ExchangeRate<'_, Currency>) {
     let rate: Decimal =
         Decimal::from_str_exact(rate.as_ref()).expect("Rate in local DB is not a decimal");
 
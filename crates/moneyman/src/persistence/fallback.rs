@@ -5,17 +5,20 @@ use rusty_money::{
     iso::{self, Currency},
     Exchange, ExchangeRate, Money,
 };
+use thiserror::Error;
 
 use super::exchange_rate::row_to_exchange_rates;
 
 /// Any error that may happen when trying to interpolate rates from its
 /// neighboring dates.
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub(crate) enum InterpolationError {
     /// If a rate is missing, and thus cannot complete the interpolation
+    #[error("a neighboring rate is missing")]
     MissingRate,
     /// If the currency being converted is the same. In this case, I don't
     /// think it's ever possible but is here because of `rusty_money`.
+    #[error("can't interpolate a currency against itself")]
     SameCurrency,
 }
 
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+
+/// When the store was last synced, as an RFC 3339 timestamp
+pub(crate) const LAST_SYNCED_AT: &str = "last_synced_at";
+/// When a sync was last attempted by a `SyncPolicy`, whether it succeeded or
+/// not, as an RFC 3339 timestamp
+#[cfg(feature = "download")]
+pub(crate) const LAST_SYNC_ATTEMPT_AT: &str = "last_sync_attempt_at";
+/// Full URL of the archive the store was last synced from
+pub(crate) const SOURCE_URL: &str = "source_url";
+
+/// Creates the key-value table for facts about the store itself, rather than
+/// its rates.
+#[cfg(feature = "download")]
+fn create_table(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS metadata (Key TEXT PRIMARY KEY, Value TEXT NOT NULL);",
+    )
+}
+
+/// Sets the value of a key, replacing the existing one
+#[cfg(feature = "download")]
+pub(crate) fn set(conn: &Connection, key: &str, value: &str) -> Result<(), rusqlite::Error> {
+    create_table(conn)?;
+
+    conn.execute(
+        "INSERT INTO metadata(Key, Value) VALUES (?1, ?2)
+            ON CONFLICT(Key) DO UPDATE SET Value = excluded.Value",
+        [key, value],
+    )
+    .map(|_| ())
+}
+
+/// Gets the value of a key. Stores synced before the table existed don't
+/// have any keys. The table isn't created here, so reading works on
+/// read-only stores too.
+pub(crate) fn get(conn: &Connection, key: &str) -> Result<Option<String>, rusqlite::Error> {
+    let has_table = conn
+        .prepare_cached("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'metadata'")?
+        .exists(())?;
+
+    if !has_table {
+        return Ok(None);
+    }
+
+    conn.query_row("SELECT Value FROM metadata WHERE Key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .optional()
+}
+
+/// Gets the value of a key holding an RFC 3339 timestamp. Unparseable values
+/// are treated as missing.
+pub(crate) fn get_time(
+    conn: &Connection,
+    key: &str,
+) -> Result<Option<DateTime<Utc>>, rusqlite::Error> {
+    Ok(get(conn, key)?
+        .and_then(|time| DateTime::parse_from_rfc3339(&time).ok())
+        .map(|time| time.with_timezone(&Utc)))
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::OpenFlags;
+
+    use super::*;
+    use crate::test_support::temp_data_dir;
+
+    #[test]
+    fn it_gets_nothing_from_read_only_stores_without_metadata() {
+        let db_path = temp_data_dir().join("eurofxref-hist.db3");
+        Connection::open(&db_path)
+            .unwrap()
+            .execute_batch("CREATE TABLE rates (Date TEXT, Interpolated BOOLEAN);")
+            .unwrap();
+
+        let conn = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).unwrap();
+
+        assert_eq!(None, get(&conn, SOURCE_URL).unwrap());
+        assert_eq!(None, get_time(&conn, LAST_SYNCED_AT).unwrap());
+    }
+
+    #[cfg(feature = "download")]
+    #[test]
+    fn it_gets_what_was_set() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        set(&conn, SOURCE_URL, "https://example.com/eurofxref-hist.zip").unwrap();
+
+        assert_eq!(
+            Some("https://example.com/eurofxref-hist.zip".to_string()),
+            get(&conn, SOURCE_URL).unwrap()
+        );
+    }
+}
@@ -1,22 +1,134 @@
-use std::path::Path;
+use std::{collections::HashSet, path::Path};
 
 use chrono::NaiveDate;
 use rusqlite::{vtab::csvtab, Connection};
 use rust_decimal::Decimal;
 use rusty_money::{iso, Exchange, Money};
+use thiserror::Error;
+
+use crate::{
+    exchange_store::Revision,
+    persistence::{
+        self,
+        fallback::{fetch_neighboring_rates, InterpolationError},
+    },
+};
+
+/// Every currency column in the ECB history, including the ones that no
+/// longer exist.
+const CSV_CURRENCIES: [&str; 41] = [
+    "USD", "JPY", "BGN", "CYP", "CZK", "DKK", "EEK", "GBP", "HUF", "LTL", "LVL", "MTL", "PLN",
+    "ROL", "RON", "SEK", "SIT", "SKK", "CHF", "ISK", "NOK", "HRK", "RUB", "TRL", "TRY", "AUD",
+    "BRL", "CAD", "CNY", "HKD", "IDR", "ILS", "INR", "KRW", "MXN", "MYR", "NZD", "PHP", "SGD",
+    "THB", "ZAR",
+];
+
+/// Any error that may happen while seeding the DB
+#[derive(Debug, Error)]
+pub(crate) enum SeedError {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    /// Missing rates couldn't be interpolated from their neighboring dates
+    #[error("unable to interpolate rates: {0}")]
+    Interpolation(#[from] InterpolationError),
+}
 
-use crate::persistence::{self, fallback::fetch_neighboring_rates};
-
-/// Seeds the DB with the history of exchange rates
-pub(crate) fn seed_db(conn: &Connection, data_dir: &Path) -> Result<(), rusqlite::Error> {
+/// Seeds the DB with the history of exchange rates. Returns the revisions
+/// ECB made to dates that were already stored.
+pub(crate) fn seed_db(conn: &Connection, data_dir: &Path) -> Result<Vec<Revision>, SeedError> {
     let csv_path = data_dir.join("eurofxref-hist.csv");
-    let interpolation_start_date = copy_from_csv(conn, &csv_path)?;
-    clean_up_na(conn)?;
-    precompute_interpolated_rates(conn, interpolation_start_date)
+    track_dependencies(conn)?;
+
+    // Either the new observations land along with every interpolated rate
+    // that depends on them, or nothing does, and the next sync starts over.
+    let tx = conn.unchecked_transaction()?;
+
+    let revisions = match copy_from_csv(&tx, &csv_path)? {
+        Copied::Created(first_date) => {
+            clean_up_na(&tx)?;
+            precompute_interpolated_rates(&tx, first_date)?;
+
+            Vec::new()
+        }
+        Copied::Inserted(inserted_dates) => {
+            let revisions = apply_revisions(&tx)?;
+            tx.execute_batch("DROP TABLE IF EXISTS vrates;")?;
+            clean_up_na(&tx)?;
+
+            let mut changed_dates: Vec<NaiveDate> = revisions
+                .iter()
+                .map(|revision| revision.date)
+                .chain(inserted_dates)
+                .collect();
+            changed_dates.sort();
+            changed_dates.dedup();
+
+            refresh_interpolated_rates(&tx, &changed_dates)?;
+
+            revisions
+        }
+    };
+
+    tx.commit()?;
+
+    Ok(revisions)
+}
+
+/// What `copy_from_csv` did to the `rates` table
+enum Copied {
+    /// `rates` didn't exist, and was created from the CSV. The first
+    /// observation is on this date.
+    Created(NaiveDate),
+    /// Observations that weren't stored before were inserted on these dates.
+    /// This includes late observations on dates that were interpolated.
+    Inserted(Vec<NaiveDate>),
+}
+
+/// Makes sure interpolated rows record the observations they were computed
+/// from, in `PrevDate` and `NextDate`. Stores created before these columns
+/// existed get them added, and filled in.
+fn track_dependencies(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let has_rates = conn
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'rates'")?
+        .exists(())?;
+    let has_dependencies = conn
+        .prepare("SELECT 1 FROM pragma_table_info('rates') WHERE name = 'PrevDate'")?
+        .exists(())?;
+
+    if !has_rates || has_dependencies {
+        return Ok(());
+    }
+
+    conn.execute_batch(
+        "
+        BEGIN;
+            ALTER TABLE rates ADD COLUMN PrevDate TEXT;
+            ALTER TABLE rates ADD COLUMN NextDate TEXT;
+
+            UPDATE rates
+                SET PrevDate =
+                        ( SELECT MAX(o.Date)
+                            FROM rates o
+                            WHERE o.Date < rates.Date
+                                AND o.Interpolated = false
+                        )
+                  , NextDate =
+                        ( SELECT MIN(o.Date)
+                            FROM rates o
+                            WHERE o.Date > rates.Date
+                                AND o.Interpolated = false
+                        )
+                WHERE Interpolated = true;
+
+            CREATE INDEX IF NOT EXISTS dependency_index ON rates(PrevDate, NextDate);
+        COMMIT;
+        ",
+    )
 }
 
-/// Creates a virtual table `vrates` from the CSV
-fn copy_from_csv(conn: &Connection, csv_path: &Path) -> Result<NaiveDate, rusqlite::Error> {
+/// Creates a virtual table `vrates` from the CSV. Runs in the caller's
+/// transaction.
+fn copy_from_csv(conn: &Connection, csv_path: &Path) -> Result<Copied, rusqlite::Error> {
     csvtab::load_module(conn)?;
 
     let latest_date_script = "
@@ -31,74 +143,90 @@ fn copy_from_csv(conn: &Connection, csv_path: &Path) -> Result<NaiveDate, rusqli
         .and_then(|mut stmt| stmt.query_row((), |row| row.get::<usize, NaiveDate>(0)));
 
     match latest_entry {
-        Ok(latest_date) => {
+        Ok(_latest_date) => {
             let script = format!(
                 "
-                BEGIN;
-                    DROP TABLE IF EXISTS vrates;
-
-                    CREATE VIRTUAL TABLE vrates
-                        USING csv
-                            ( filename={}
-                            , header=yes
-                            );
-
-                    INSERT INTO rates
-                        SELECT Date
-                             , USD
-                             , JPY
-                             , BGN
-                             , CYP
-                             , CZK
-                             , DKK
-                             , EEK
-                             , GBP
-                             , HUF
-                             , LTL
-                             , LVL
-                             , MTL
-                             , PLN
-                             , ROL
-                             , RON
-                             , SEK
-                             , SIT
-                             , SKK
-                             , CHF
-                             , ISK
-                             , NOK
-                             , HRK
-                             , RUB
-                             , TRL
-                             , TRY
-                             , AUD
-                             , BRL
-                             , CAD
-                             , CNY
-                             , HKD
-                             , IDR
-                             , ILS
-                             , INR
-                             , KRW
-                             , MXN
-                             , MYR
-                             , NZD
-                             , PHP
-                             , SGD
-                             , THB
-                             , ZAR
-                             , false
-                            FROM vrates
-                            WHERE Date >= '{}'
-                            ORDER BY Date DESC;
-                COMMIT;
+                DROP TABLE IF EXISTS vrates;
+
+                CREATE VIRTUAL TABLE vrates
+                    USING csv
+                        ( filename={}
+                        , header=yes
+                        );
+
+                DELETE FROM rates
+                    WHERE Interpolated = true
+                        AND Date IN (SELECT Date FROM vrates);
+
+                INSERT INTO rates(Date, {}, Interpolated)
+                    SELECT Date
+                         , USD
+                         , JPY
+                         , BGN
+                         , CYP
+                         , CZK
+                         , DKK
+                         , EEK
+                         , GBP
+                         , HUF
+                         , LTL
+                         , LVL
+                         , MTL
+                         , PLN
+                         , ROL
+                         , RON
+                         , SEK
+                         , SIT
+                         , SKK
+                         , CHF
+                         , ISK
+                         , NOK
+                         , HRK
+                         , RUB
+                         , TRL
+                         , TRY
+                         , AUD
+                         , BRL
+                         , CAD
+                         , CNY
+                         , HKD
+                         , IDR
+                         , ILS
+                         , INR
+                         , KRW
+                         , MXN
+                         , MYR
+                         , NZD
+                         , PHP
+                         , SGD
+                         , THB
+                         , ZAR
+                         , false
+                        FROM vrates
+                        WHERE Date NOT IN (SELECT Date FROM rates)
+                        ORDER BY Date DESC;
 
                 ",
                 csv_path.to_str().expect("expected a UTF-8 path"),
-                latest_date.succ_opt().unwrap()
+                CSV_CURRENCIES.join(", ")
             );
 
+            let mut observed_dates_stmt =
+                conn.prepare("SELECT Date FROM rates WHERE Interpolated = false")?;
+            let observed_dates = observed_dates_stmt
+                .query_map((), |row| row.get::<usize, NaiveDate>(0))?
+                .collect::<Result<HashSet<_>, rusqlite::Error>>()?;
+
             conn.execute_batch(script.as_str())?;
-            Ok(latest_date)
+
+            let mut new_dates_stmt =
+                conn.prepare("SELECT Date FROM rates WHERE Interpolated = false")?;
+            let inserted_dates = new_dates_stmt
+                .query_map((), |row| row.get::<usize, NaiveDate>(0))?
+                .filter(|date| !matches!(date, Ok(date) if observed_dates.contains(date)))
+                .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+            Ok(Copied::Inserted(inserted_dates))
         }
         Err(err @ rusqlite::Error::QueryReturnedNoRows)
         | Err(err @ rusqlite::Error::SqliteFailure(_, _)) => {
@@ -107,34 +235,38 @@ fn copy_from_csv(conn: &Connection, csv_path: &Path) -> Result<NaiveDate, rusqli
                     "no such table: rates" => {
                         let script = format!(
                             "
-                            BEGIN;
-                                DROP TABLE IF EXISTS vrates;
-                                DROP TABLE IF EXISTS rates;
+                            DROP TABLE IF EXISTS vrates;
+                            DROP TABLE IF EXISTS rates;
 
-                                CREATE VIRTUAL TABLE vrates
-                                    USING csv
-                                        ( filename={}
-                                        , header=yes
-                                        );
+                            CREATE VIRTUAL TABLE vrates
+                                USING csv
+                                    ( filename={}
+                                    , header=yes
+                                    );
 
-                                CREATE TABLE rates AS SELECT * FROM vrates;
+                            CREATE TABLE rates AS SELECT * FROM vrates;
 
-                                ALTER TABLE rates ADD COLUMN Interpolated BOOLEAN;
-                                ALTER TABLE rates DROP COLUMN \"\";
+                            ALTER TABLE rates ADD COLUMN Interpolated BOOLEAN;
+                            ALTER TABLE rates ADD COLUMN PrevDate TEXT;
+                            ALTER TABLE rates ADD COLUMN NextDate TEXT;
+                            ALTER TABLE rates DROP COLUMN \"\";
 
-                                UPDATE rates SET Interpolated = false;
+                            UPDATE rates SET Interpolated = false;
 
-                                CREATE UNIQUE INDEX date_index ON rates(Date);
-                                CREATE INDEX date_interpolated_index ON rates(Date, Interpolated);
+                            CREATE UNIQUE INDEX date_index ON rates(Date);
+                            CREATE INDEX date_interpolated_index ON rates(Date, Interpolated);
+                            CREATE INDEX dependency_index ON rates(PrevDate, NextDate);
 
-                                DROP TABLE vrates;
-                            COMMIT;
+                            DROP TABLE vrates;
                             ",
                             csv_path.to_str().expect("expected a UTF-8 path")
                         );
 
                         conn.execute_batch(script.as_str())?;
-                        Ok(NaiveDate::from_ymd_opt(1999, 1, 4).unwrap())
+                        conn.query_row("SELECT MIN(Date) FROM rates", (), |row| {
+                            row.get::<usize, NaiveDate>(0)
+                        })
+                        .map(Copied::Created)
                     }
 
                     _ => Err(rusqlite::Error::SqliteFailure(error1, Some(err_str))),
@@ -147,27 +279,136 @@ fn copy_from_csv(conn: &Connection, csv_path: &Path) -> Result<NaiveDate, rusqli
     }
 }
 
-/// Sets rows with "N/A" to actual NULL values
-fn clean_up_na(conn: &Connection) -> Result<(), rusqlite::Error> {
-    let currencies = [
-        "USD", "JPY", "BGN", "CYP", "CZK", "DKK", "EEK", "GBP", "HUF", "LTL", "LVL", "MTL", "PLN",
-        "ROL", "RON", "SEK", "SIT", "SKK", "CHF", "ISK", "NOK", "HRK", "RUB", "TRL", "TRY", "AUD",
-        "BRL", "CAD", "CNY", "HKD", "IDR", "ILS", "INR", "KRW", "MXN", "MYR", "NZD", "PHP", "SGD",
-        "THB", "ZAR",
-    ];
+/// Compares the CSV in `vrates` against the observed (non-interpolated) rows
+/// already stored, and overwrites the rates ECB has since corrected. Only
+/// dates that exist on both sides are compared. Runs in the caller's
+/// transaction.
+fn apply_revisions(conn: &Connection) -> Result<Vec<Revision>, rusqlite::Error> {
+    let has_vrates = conn
+        .prepare("SELECT 1 FROM sqlite_master WHERE name = 'vrates'")?
+        .exists(())?;
+
+    if !has_vrates {
+        return Ok(Vec::new());
+    }
+
+    let mut revisions = Vec::new();
+
+    for currency in CSV_CURRENCIES {
+        let mut changed_stmt = conn.prepare(
+            format!(
+                "
+                SELECT v.Date, NULLIF(r.{currency}, 'N/A'), NULLIF(v.{currency}, 'N/A')
+                    FROM vrates v
+                    JOIN rates r ON r.Date = v.Date
+                    WHERE r.Interpolated = false
+                        AND NULLIF(r.{currency}, 'N/A') IS NOT NULLIF(v.{currency}, 'N/A')
+                "
+            )
+            .as_ref(),
+        )?;
+
+        let changed = changed_stmt
+            .query_map((), |row| {
+                Ok((
+                    row.get::<usize, NaiveDate>(0)?,
+                    row.get::<usize, Option<String>>(1)?,
+                    row.get::<usize, Option<String>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+        let mut update_stmt =
+            conn.prepare(format!("UPDATE rates SET {currency} = ?1 WHERE Date = ?2").as_ref())?;
+
+        for (date, old, new) in changed {
+            update_stmt.execute((&new, date))?;
+
+            revisions.push(Revision {
+                date,
+                currency: currency.to_string(),
+                old: old.and_then(|rate| Decimal::from_str_exact(rate.as_ref()).ok()),
+                new: new.and_then(|rate| Decimal::from_str_exact(rate.as_ref()).ok()),
+            });
+        }
+    }
+
+    revisions.sort_by(|a, b| (a.date, &a.currency).cmp(&(b.date, &b.currency)));
 
-    let statements = currencies
+    Ok(revisions)
+}
+
+/// Recomputes the interpolated rows that depend on the observations on
+/// `changed_dates`, and interpolates the dates that are left without a row.
+///
+/// An interpolated row depends on an observation if it was computed from it,
+/// or if the observation now falls between the two it was computed from.
+fn refresh_interpolated_rates(
+    conn: &Connection,
+    changed_dates: &[NaiveDate],
+) -> Result<(), SeedError> {
+    for date in changed_dates {
+        conn.execute(
+            "
+            DELETE FROM rates
+                WHERE Interpolated = true
+                    AND PrevDate <= ?1
+                    AND NextDate >= ?1
+            ",
+            [date],
+        )?;
+
+        let prev_date = conn
+            .query_row(
+                "SELECT MAX(Date) FROM rates WHERE Date < ?1 AND Interpolated = false",
+                [date],
+                |row| row.get::<usize, Option<NaiveDate>>(0),
+            )?
+            .unwrap_or(*date);
+        let next_date = conn
+            .query_row(
+                "SELECT MIN(Date) FROM rates WHERE Date > ?1 AND Interpolated = false",
+                [date],
+                |row| row.get::<usize, Option<NaiveDate>>(0),
+            )?
+            .unwrap_or(*date);
+
+        precompute_interpolated_rates_between(conn, prev_date, next_date)?;
+    }
+
+    Ok(())
+}
+
+/// Sets rows with "N/A" to actual NULL values. Runs in the caller's
+/// transaction.
+fn clean_up_na(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let statements = CSV_CURRENCIES
         .map(|c| format!("UPDATE rates SET {c} = null WHERE {c} = 'N/A';"))
         .join("\n");
 
-    let statements = format!("BEGIN; \n{statements}\nCOMMIT;");
     (*conn).execute_batch(statements.as_ref())
 }
 
 fn precompute_interpolated_rates(
     conn: &Connection,
     start_date: NaiveDate,
-) -> Result<(), rusqlite::Error> {
+) -> Result<(), SeedError> {
+    let mut latest_date_statement =
+        conn.prepare("SELECT Date FROM rates ORDER BY Date DESC LIMIT 1")?;
+
+    let latest_date = latest_date_statement.query_row((), |row| row.get::<usize, NaiveDate>(0))?;
+
+    precompute_interpolated_rates_between(conn, start_date, latest_date)
+}
+
+/// Interpolates every date strictly between `start_date` and `end_date`
+/// that doesn't have an observation. Interpolated rows that already exist are
+/// overwritten.
+fn precompute_interpolated_rates_between(
+    conn: &Connection,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<(), SeedError> {
     let currencies = [
         iso::USD,
         iso::JPY,
@@ -205,24 +446,29 @@ fn precompute_interpolated_rates(
     ];
 
     let selectable_columns = currencies.map(|c| c.iso_alpha_code).join(", ");
-    let mut latest_date_statement =
-        conn.prepare("SELECT Date FROM rates ORDER BY Date DESC LIMIT 1")?;
+    let excluded_columns = currencies
+        .map(|c| format!("excluded.{}", c.iso_alpha_code))
+        .join(", ");
 
-    let latest_date = latest_date_statement.query_row((), |row| row.get::<usize, NaiveDate>(0))?;
+    let mut observed_dates_stmt = conn
+        .prepare("SELECT Date FROM rates WHERE Date > ?1 AND Date < ?2 AND Interpolated = false")?;
+    let observed_dates = observed_dates_stmt
+        .query_map((start_date, end_date), |row| row.get::<usize, NaiveDate>(0))?
+        .collect::<Result<HashSet<_>, rusqlite::Error>>()?;
 
     start_date
         .iter_days()
         // Skip the first date since the first date should always have a rate
         .skip(1)
-        // Take until before the latest date since it also should always have
+        // Take until before the end date since it also should always have
         // a rate
-        .take_while(|date| *date < latest_date)
+        .take_while(|date| *date < end_date)
+        .filter(|date| !observed_dates.contains(date))
         .map(|date| {
             let neighbors = fetch_neighboring_rates(conn, &currencies, date)?;
+            let (prev_date, next_date) = (neighbors.prev_date, neighbors.next_date);
 
-            // FIXME: Need to find a way to get rid of this `.expect()`
-            let rates = persistence::fallback::interpolate_rates(&currencies, neighbors)
-                .expect("Unable to interpolate rates");
+            let rates = persistence::fallback::interpolate_rates(&currencies, neighbors)?;
 
             let exchange = rates.iter().fold(Exchange::new(), |mut exchange, rate| {
                 exchange.set_rate(rate);
@@ -250,16 +496,206 @@ fn precompute_interpolated_rates(
 
             let script = format!(
                 "
-                INSERT INTO rates(Date, Interpolated, {selectable_columns})
-                    VALUES ('{}', true, {})
-                    ON CONFLICT DO NOTHING
+                INSERT INTO rates(Date, Interpolated, PrevDate, NextDate, {selectable_columns})
+                    VALUES ('{}', true, '{}', '{}', {})
+                    ON CONFLICT(Date) DO UPDATE
+                        SET ({selectable_columns}, PrevDate, NextDate) =
+                            ({excluded_columns}, excluded.PrevDate, excluded.NextDate)
+                        WHERE Interpolated = true
                 ",
-                date, currency_values_str
+                date, prev_date, next_date, currency_values_str
             );
 
-            conn.execute_batch(script.as_str())
+            conn.execute_batch(script.as_str())?;
+
+            Ok(())
         })
-        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+        .collect::<Result<Vec<_>, SeedError>>()?;
 
     Ok(())
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::test_support::{date, fixture_csv, seed_with, temp_data_dir};
+
+    pub(crate) fn usd_on(conn: &Connection, date: &str) -> (String, bool) {
+        conn.query_row(
+            "SELECT USD, Interpolated FROM rates WHERE Date = ?1",
+            [date],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn it_applies_revisions_to_stored_dates() {
+        let data_dir = temp_data_dir();
+        let conn = Connection::open(data_dir.join("eurofxref-hist.db3")).unwrap();
+        let csv = fixture_csv();
+
+        assert_eq!(Vec::<Revision>::new(), seed_with(&conn, &data_dir, &csv));
+
+        let (interpolated_before, is_interpolated) = usd_on(&conn, "2023-05-06");
+        assert!(is_interpolated);
+
+        let revised_csv = csv.replace("2023-05-05,1.1014,", "2023-05-05,1.2014,");
+        let revisions = seed_with(&conn, &data_dir, &revised_csv);
+
+        assert_eq!(
+            vec![Revision {
+                date: date("2023-05-05"),
+                currency: "USD".to_string(),
+                old: Some(Decimal::from_i128_with_scale(11014, 4)),
+                new: Some(Decimal::from_i128_with_scale(12014, 4)),
+            }],
+            revisions
+        );
+        assert_eq!(("1.2014".to_string(), false), usd_on(&conn, "2023-05-05"));
+
+        let (interpolated_after, is_interpolated) = usd_on(&conn, "2023-05-06");
+        assert!(is_interpolated);
+        assert_ne!(interpolated_before, interpolated_after);
+    }
+
+    #[test]
+    fn it_reports_no_revisions_when_only_new_dates_arrive() {
+        let data_dir = temp_data_dir();
+        let conn = Connection::open(data_dir.join("eurofxref-hist.db3")).unwrap();
+        let csv = fixture_csv();
+        let older_csv: String = csv
+            .lines()
+            .enumerate()
+            .filter(|(index, _)| *index != 1)
+            .map(|(_, line)| format!("{line}\n"))
+            .collect();
+
+        seed_with(&conn, &data_dir, &older_csv);
+
+        assert_eq!(Vec::<Revision>::new(), seed_with(&conn, &data_dir, &csv));
+        assert_eq!(("1.0892".to_string(), false), usd_on(&conn, "2023-05-12"));
+    }
+
+    #[test]
+    fn it_recomputes_interpolations_around_late_observations() {
+        let data_dir = temp_data_dir();
+        let conn = Connection::open(data_dir.join("eurofxref-hist.db3")).unwrap();
+        let csv = fixture_csv();
+        let csv_without_monday: String = csv
+            .lines()
+            .filter(|line| !line.starts_with("2023-05-08"))
+            .map(|line| format!("{line}\n"))
+            .collect();
+
+        seed_with(&conn, &data_dir, &csv_without_monday);
+
+        let dependencies = |date: &str| -> (String, String) {
+            conn.query_row(
+                "SELECT PrevDate, NextDate FROM rates WHERE Date = ?1",
+                [date],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+        };
+
+        let (monday_before, is_interpolated) = usd_on(&conn, "2023-05-08");
+        let (saturday_before, _) = usd_on(&conn, "2023-05-06");
+        let (untouched_before, _) = usd_on(&conn, "2023-04-29");
+        assert!(is_interpolated);
+        assert_eq!(
+            ("2023-05-05".to_string(), "2023-05-09".to_string()),
+            dependencies("2023-05-06")
+        );
+
+        // ECB publishes the missing Monday afterwards
+        assert_eq!(Vec::<Revision>::new(), seed_with(&conn, &data_dir, &csv));
+
+        let (monday_after, is_interpolated) = usd_on(&conn, "2023-05-08");
+        assert!(!is_interpolated);
+        assert_eq!("1.1037", monday_after);
+        assert_ne!(monday_before, monday_after);
+
+        let (saturday_after, is_interpolated) = usd_on(&conn, "2023-05-06");
+        assert!(is_interpolated);
+        assert_ne!(saturday_before, saturday_after);
+        assert_eq!(
+            ("2023-05-05".to_string(), "2023-05-08".to_string()),
+            dependencies("2023-05-06")
+        );
+
+        // Interpolations that don't depend on the new observation stay put
+        assert_eq!(untouched_before, usd_on(&conn, "2023-04-29").0);
+    }
+
+    #[test]
+    fn it_keeps_late_observations_for_the_next_sync_if_the_refresh_fails() {
+        let data_dir = temp_data_dir();
+        let conn = Connection::open(data_dir.join("eurofxref-hist.db3")).unwrap();
+        let csv = fixture_csv();
+        let csv_without_monday: String = csv
+            .lines()
+            .filter(|line| !line.starts_with("2023-05-08"))
+            .map(|line| format!("{line}\n"))
+            .collect();
+
+        seed_with(&conn, &data_dir, &csv_without_monday);
+        let (saturday_before, _) = usd_on(&conn, "2023-05-06");
+
+        // Interpolating fails, after the missing Monday was copied in
+        conn.execute_batch(
+            "
+            CREATE TRIGGER fail_interpolation BEFORE INSERT ON rates
+                WHEN NEW.Interpolated = true
+                BEGIN
+                    SELECT RAISE(ABORT, 'interpolation failed');
+                END;
+            ",
+        )
+        .unwrap();
+        std::fs::write(data_dir.join("eurofxref-hist.csv"), &csv).unwrap();
+        assert!(seed_db(&conn, &data_dir).is_err());
+
+        assert!(usd_on(&conn, "2023-05-08").1);
+        assert_eq!(saturday_before, usd_on(&conn, "2023-05-06").0);
+
+        conn.execute_batch("DROP TRIGGER fail_interpolation;")
+            .unwrap();
+        seed_with(&conn, &data_dir, &csv);
+
+        assert_eq!(("1.1037".to_string(), false), usd_on(&conn, "2023-05-08"));
+        assert_ne!(saturday_before, usd_on(&conn, "2023-05-06").0);
+    }
+
+    #[test]
+    fn it_tracks_dependencies_of_stores_created_without_them() {
+        let data_dir = temp_data_dir();
+        let conn = Connection::open(data_dir.join("eurofxref-hist.db3")).unwrap();
+
+        seed_with(&conn, &data_dir, &fixture_csv());
+        conn.execute_batch(
+            "
+            DROP INDEX dependency_index;
+            ALTER TABLE rates DROP COLUMN PrevDate;
+            ALTER TABLE rates DROP COLUMN NextDate;
+            ",
+        )
+        .unwrap();
+
+        track_dependencies(&conn).unwrap();
+
+        let dependencies: (String, String) = conn
+            .query_row(
+                "SELECT PrevDate, NextDate FROM rates WHERE Date = '2023-05-07'",
+                (),
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            ("2023-05-05".to_string(), "2023-05-08".to_string()),
+            dependencies
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::NaiveDate;
+use rusqlite::Connection;
+
+use crate::persistence::exchange_rate::currency_columns;
+
+/// Counts the observed, and interpolated rows, in that order
+pub(crate) fn count_rows(conn: &Connection) -> Result<(usize, usize), rusqlite::Error> {
+    conn.query_row(
+        "
+            SELECT
+                COALESCE(SUM(Interpolated = false), 0),
+                COALESCE(SUM(Interpolated = true), 0)
+                FROM rates
+        ",
+        (),
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// First, and latest dates with rates published by ECB
+pub(crate) fn observed_date_range(
+    conn: &Connection,
+) -> Result<Option<(NaiveDate, NaiveDate)>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT MIN(Date), MAX(Date) FROM rates WHERE Interpolated = false",
+        (),
+        |row| {
+            let first = row.get::<usize, Option<NaiveDate>>(0)?;
+            let latest = row.get::<usize, Option<NaiveDate>>(1)?;
+
+            Ok(first.zip(latest))
+        },
+    )
+}
+
+/// Every currency column, and the first and last dates ECB published a rate
+/// for it. Currencies without a single rate are left out.
+pub(crate) fn currency_date_ranges(
+    conn: &Connection,
+) -> Result<Vec<(String, NaiveDate, NaiveDate)>, rusqlite::Error> {
+    let currencies = currency_columns(conn)?;
+
+    if currencies.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // A single scan for every currency instead of one query each
+    let aggregates = currencies
+        .iter()
+        .map(|currency| {
+            format!(
+                "MIN(CASE WHEN {currency} IS NOT NULL THEN Date END), MAX(CASE WHEN {currency} IS NOT NULL THEN Date END)"
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    conn.query_row(
+        format!("SELECT {aggregates} FROM rates WHERE Interpolated = false").as_ref(),
+        (),
+        |row| {
+            currencies
+                .iter()
+                .enumerate()
+                .filter_map(|(index, currency)| {
+                    let first = row.get::<usize, Option<NaiveDate>>(index * 2);
+                    let last = row.get::<usize, Option<NaiveDate>>(index * 2 + 1);
+
+                    match (first, last) {
+                        (Ok(Some(first)), Ok(Some(last))) => {
+                            Some(Ok((currency.clone(), first, last)))
+                        }
+                        (Err(err), _) | (_, Err(err)) => Some(Err(err)),
+                        _ => None,
+                    }
+                })
+                .collect()
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_has_no_date_ranges_without_currency_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "
+            CREATE TABLE rates (Date TEXT, Interpolated BOOLEAN, PrevDate TEXT, NextDate TEXT);
+            INSERT INTO rates VALUES ('2023-05-04', false, NULL, NULL);
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Vec::<(String, NaiveDate, NaiveDate)>::new(),
+            currency_date_ranges(&conn).unwrap()
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rusty_money::{iso::Currency, Money};
+
+/// How much worse than the ECB mid rate a customer's rate is, e.g a 1%
+/// spread turns a mid rate of 1.10 into 1.089. The whole spread is charged on
+/// top of the mid rate, rather than half of it on each side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Spread(Decimal);
+
+impl Spread {
+    /// e.g `Spread::percent(dec!(1.5))` for 1.5%
+    pub fn percent(percent: Decimal) -> Self {
+        Spread(percent / Decimal::ONE_HUNDRED)
+    }
+
+    /// e.g `Spread::basis_points(dec!(150))` for 1.5%
+    pub fn basis_points(basis_points: Decimal) -> Self {
+        Spread(basis_points / Decimal::from(10_000))
+    }
+
+    /// The spread as a fraction of the mid rate, e.g 0.015 for 1.5%
+    pub fn fraction(&self) -> Decimal {
+        self.0
+    }
+
+    /// Applies the spread to a mid rate
+    pub fn apply(&self, mid_rate: Decimal) -> Decimal {
+        mid_rate * (Decimal::ONE - self.0)
+    }
+}
+
+/// A fee charged on the amount being converted, before it's converted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fee {
+    /// A flat amount in the source currency
+    Fixed(Decimal),
+    /// A percentage of the amount, e.g `Fee::Percent(dec!(0.5))` for 0.5%
+    Percent(Decimal),
+}
+
+impl Fee {
+    /// How much the fee takes from `amount`
+    pub fn of(&self, amount: Decimal) -> Decimal {
+        match self {
+            Fee::Fixed(fee) => *fee,
+            Fee::Percent(percent) => amount * percent / Decimal::ONE_HUNDRED,
+        }
+    }
+}
+
+/// What a customer is charged on top of the ECB mid rates. The default
+/// charges nothing, so quotes come out at the mid rate.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pricing {
+    /// Spread of every pair without one in `pair_spreads`
+    pub spread: Spread,
+    /// Spreads of specific pairs, by the ISO alpha codes of the source, and
+    /// target currencies. A pair only matches in the given direction.
+    pub pair_spreads: Vec<((&'static str, &'static str), Spread)>,
+    /// Every fee charged, in the source currency
+    pub fees: Vec<Fee>,
+}
+
+impl Pricing {
+    /// The spread charged when converting `from` to `to`
+    pub fn spread_of(&self, from: &Currency, to: &Currency) -> Spread {
+        self.pair_spreads
+            .iter()
+            .find(|((pair_from, pair_to), _)| {
+                *pair_from == from.iso_alpha_code && *pair_to == to.iso_alpha_code
+            })
+            .map_or(self.spread, |(_, spread)| *spread)
+    }
+
+    /// The sum of every fee on `amount`
+    pub fn fees_of(&self, amount: Decimal) -> Decimal {
+        self.fees.iter().map(|fee| fee.of(amount)).sum()
+    }
+}
+
+/// A conversion at the mid rate, next to what the customer actually gets
+/// after the spread, and fees.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Quote<'c> {
+    /// Date of the rate used
+    pub date: NaiveDate,
+    /// Units of the target currency per unit of the source currency
+    pub mid_rate: Decimal,
+    /// `mid_rate` after the spread
+    pub effective_rate: Decimal,
+    pub spread: Spread,
+    /// The amount converted at the mid rate, without any fees
+    pub mid_amount: Money<'c, Currency>,
+    /// Every fee, in the source currency
+    pub fees: Money<'c, Currency>,
+    /// What's left of the amount after fees, converted at `effective_rate`
+    pub customer_amount: Money<'c, Currency>,
+}
+
+#[cfg(test)]
+mod tests {
+    use rusty_money::iso;
+
+    use super::*;
+
+    #[test]
+    fn it_applies_spreads_in_either_unit() {
+        let mid_rate = Decimal::from_i128_with_scale(110, 2);
+
+        assert_eq!(
+            Decimal::from_i128_with_scale(1089, 3),
+            Spread::percent(Decimal::ONE).apply(mid_rate)
+        );
+        assert_eq!(
+            Spread::percent(Decimal::ONE),
+            Spread::basis_points(Decimal::ONE_HUNDRED)
+        );
+        assert_eq!(mid_rate, Spread::default().apply(mid_rate));
+    }
+
+    #[test]
+    fn it_picks_the_spread_of_a_pair() {
+        let pricing = Pricing {
+            spread: Spread::percent(Decimal::ONE),
+            pair_spreads: vec![(("USD", "JPY"), Spread::basis_points(Decimal::from(25)))],
+            fees: vec![
+                Fee::Fixed(Decimal::TWO),
+                Fee::Percent(Decimal::from_i128_with_scale(5, 1)),
+            ],
+        };
+
+        assert_eq!(
+            Spread::basis_points(Decimal::from(25)),
+            pricing.spread_of(iso::USD, iso::JPY)
+        );
+        assert_eq!(
+            Spread::percent(Decimal::ONE),
+            pricing.spread_of(iso::JPY, iso::USD)
+        );
+        assert_eq!(Decimal::from(7), pricing.fees_of(Decimal::from(1000)));
+    }
+}
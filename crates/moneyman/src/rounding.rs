@@ -0,0 +1,164 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use rusty_money::{iso::Currency, Money};
+
+/// How a converted amount is rounded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Keeps every digit of the conversion
+    #[default]
+    Exact,
+    /// Rounds halves to the nearest even digit, also known as banker's
+    /// rounding. e.g 2.345 -> 2.34, and 2.355 -> 2.36
+    HalfEven,
+    /// Rounds halves away from zero. e.g 2.345 -> 2.35, and -2.345 -> -2.35
+    HalfUp,
+    /// Rounds towards negative infinity. e.g 2.349 -> 2.34, and -2.341 -> -2.35
+    Floor,
+    /// Rounds towards positive infinity. e.g 2.341 -> 2.35, and -2.349 -> -2.34
+    Ceiling,
+    /// Drops the extra digits. e.g 2.349 -> 2.34, and -2.349 -> -2.34
+    Truncate,
+}
+
+impl RoundingMode {
+    /// Every mode, in the order they're documented
+    pub const ALL: [RoundingMode; 6] = [
+        RoundingMode::Exact,
+        RoundingMode::HalfEven,
+        RoundingMode::HalfUp,
+        RoundingMode::Floor,
+        RoundingMode::Ceiling,
+        RoundingMode::Truncate,
+    ];
+
+    /// Kebab case name of the mode, e.g `half-even`
+    pub fn name(&self) -> &'static str {
+        match self {
+            RoundingMode::Exact => "exact",
+            RoundingMode::HalfEven => "half-even",
+            RoundingMode::HalfUp => "half-up",
+            RoundingMode::Floor => "floor",
+            RoundingMode::Ceiling => "ceiling",
+            RoundingMode::Truncate => "truncate",
+        }
+    }
+
+    /// Finds a mode by its `name`, ignoring case
+    pub fn from_name(name: &str) -> Option<RoundingMode> {
+        RoundingMode::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(name))
+    }
+
+    fn strategy(&self) -> Option<RoundingStrategy> {
+        match self {
+            RoundingMode::Exact => None,
+            RoundingMode::HalfEven => Some(RoundingStrategy::MidpointNearestEven),
+            RoundingMode::HalfUp => Some(RoundingStrategy::MidpointAwayFromZero),
+            RoundingMode::Floor => Some(RoundingStrategy::ToNegativeInfinity),
+            RoundingMode::Ceiling => Some(RoundingStrategy::ToPositiveInfinity),
+            RoundingMode::Truncate => Some(RoundingStrategy::ToZero),
+        }
+    }
+}
+
+/// How, and to how many decimal places converted amounts are rounded. The
+/// default keeps them exact.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rounding {
+    pub mode: RoundingMode,
+    /// Decimal places to round to. `None` uses the minor units of the target
+    /// currency, e.g 2 for USD, or 0 for JPY.
+    pub precision: Option<u32>,
+}
+
+impl Rounding {
+    /// Rounds to the minor units of the currency with `mode`
+    pub fn minor_units(mode: RoundingMode) -> Self {
+        Rounding {
+            mode,
+            precision: None,
+        }
+    }
+
+    /// Rounds an amount of `currency`
+    pub fn round_amount(&self, amount: Decimal, currency: &Currency) -> Decimal {
+        match self.mode.strategy() {
+            Some(strategy) => {
+                amount.round_dp_with_strategy(self.precision.unwrap_or(currency.exponent), strategy)
+            }
+            None => amount,
+        }
+    }
+
+    /// Rounds `money` in its own currency
+    pub fn round<'c>(&self, money: Money<'c, Currency>) -> Money<'c, Currency> {
+        let currency = money.currency();
+
+        Money::from_decimal(self.round_amount(*money.amount(), currency), currency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusty_money::iso;
+
+    use super::*;
+
+    fn round(mode: RoundingMode, amount: &str) -> String {
+        Rounding::minor_units(mode)
+            .round_amount(amount.parse().unwrap(), iso::USD)
+            .to_string()
+    }
+
+    #[test]
+    fn it_rounds_with_every_mode() {
+        let cases = [
+            (RoundingMode::Exact, ["2.345", "2.355", "-2.345", "2.341"]),
+            (RoundingMode::HalfEven, ["2.34", "2.36", "-2.34", "2.34"]),
+            (RoundingMode::HalfUp, ["2.35", "2.36", "-2.35", "2.34"]),
+            (RoundingMode::Floor, ["2.34", "2.35", "-2.35", "2.34"]),
+            (RoundingMode::Ceiling, ["2.35", "2.36", "-2.34", "2.35"]),
+            (RoundingMode::Truncate, ["2.34", "2.35", "-2.34", "2.34"]),
+        ];
+
+        for (mode, expected) in cases {
+            assert_eq!(
+                expected.to_vec(),
+                ["2.345", "2.355", "-2.345", "2.341"].map(|amount| round(mode, amount)),
+                "{}",
+                mode.name()
+            );
+        }
+    }
+
+    #[test]
+    fn it_rounds_to_minor_units_unless_told_otherwise() {
+        let jpy = Money::from_decimal("3044.5833333".parse().unwrap(), iso::JPY);
+
+        assert_eq!(
+            Money::from_decimal(Decimal::from(3045), iso::JPY),
+            Rounding::minor_units(RoundingMode::HalfEven).round(jpy.clone())
+        );
+        assert_eq!(
+            Money::from_decimal("3044.583".parse().unwrap(), iso::JPY),
+            Rounding {
+                mode: RoundingMode::HalfUp,
+                precision: Some(3),
+            }
+            .round(jpy)
+        );
+    }
+
+    #[test]
+    fn it_finds_modes_by_name() {
+        assert_eq!(
+            Some(RoundingMode::HalfEven),
+            RoundingMode::from_name("Half-Even")
+        );
+        assert!(RoundingMode::ALL
+            .iter()
+            .all(|mode| RoundingMode::from_name(mode.name()) == Some(*mode)));
+        assert_eq!(None, RoundingMode::from_name("bankers"));
+    }
+}
@@ -0,0 +1,62 @@
+//! Fixtures shared by the tests of every module. None of them touch the
+//! network, so they're available with or without the `download` feature.
+
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use rand::distributions::{Alphanumeric, DistString};
+use rusqlite::Connection;
+
+use crate::{
+    exchange_store::{ExchangeStore, Revision},
+    persistence::seed::seed_db,
+};
+
+/// Parses a `YYYY-MM-DD` date
+pub(crate) fn date(date: &str) -> NaiveDate {
+    date.parse().unwrap()
+}
+
+/// Creates an empty directory under the system's temp directory
+pub(crate) fn temp_data_dir() -> PathBuf {
+    let rand_str = Alphanumeric.sample_string(&mut rand::thread_rng(), 16);
+    let data_dir = std::env::temp_dir().join(format!("moneyman_{}", rand_str));
+
+    std::fs::create_dir(&data_dir).expect("failed to create test directory");
+
+    data_dir
+}
+
+/// The header and the 11 latest rows of the test history, from 2023-04-27
+/// to 2023-05-12.
+pub(crate) fn fixture_csv() -> String {
+    let csv = std::fs::read_to_string(
+        PathBuf::new()
+            .join("..")
+            .join("..")
+            .join("test_data")
+            .join("indexed")
+            .join("eurofxref-hist.csv"),
+    )
+    .expect("missing test history");
+
+    csv.lines()
+        .take(12)
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+/// Writes `csv` as the history of `data_dir`, and seeds the store with it
+pub(crate) fn seed_with(conn: &Connection, data_dir: &Path, csv: &str) -> Vec<Revision> {
+    std::fs::write(data_dir.join("eurofxref-hist.csv"), csv).unwrap();
+    seed_db(conn, data_dir).unwrap()
+}
+
+/// Creates a store seeded with `fixture_csv`
+pub(crate) fn fixture_store() -> ExchangeStore {
+    let data_dir = temp_data_dir();
+    let conn = Connection::open(data_dir.join("eurofxref-hist.db3")).unwrap();
+    seed_with(&conn, &data_dir, &fixture_csv());
+
+    ExchangeStore::open(data_dir).unwrap()
+}
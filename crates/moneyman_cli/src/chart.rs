@@ -0,0 +1,663 @@
+use std::{fmt::Write, path::Path, path::PathBuf};
+
+use chrono::{Days, NaiveDate};
+use moneyman::{DatedRate, RateStatus};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+use crate::error::CliError;
+
+/// Bits of the braille dots in a cell, by column, and row. A cell is 2 dots
+/// wide, and 4 dots tall.
+const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+/// Cells of the terminal, each drawn as a braille character
+struct Canvas {
+    width: usize,
+    height: usize,
+    cells: Vec<u32>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            cells: vec![0; width * height],
+        }
+    }
+
+    /// Sets the dot at `x`, and `y` dots from the top left
+    fn set(&mut self, x: usize, y: usize) {
+        if x < self.width * 2 && y < self.height * 4 {
+            self.cells[(y / 4) * self.width + x / 2] |= DOTS[x % 2][y % 4];
+        }
+    }
+
+    /// Draws a line between two dots. Dotted lines skip every other dot.
+    fn line(&mut self, from: (usize, usize), to: (usize, usize), dotted: bool) {
+        let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+        let (to_x, to_y) = (to.0 as i64, to.1 as i64);
+        let (dx, dy) = ((to_x - x).abs(), -(to_y - y).abs());
+        let (step_x, step_y) = ((to_x - x).signum(), (to_y - y).signum());
+        let mut error = dx + dy;
+
+        for step in 0.. {
+            if !dotted || step % 2 == 0 {
+                self.set(x as usize, y as usize);
+            }
+            if (x, y) == (to_x, to_y) {
+                break;
+            }
+
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    fn rows(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.width)
+            .map(|row| {
+                row.iter()
+                    .map(|&bits| char::from_u32(0x2800 + bits).expect("braille is valid"))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Draws `series`, which is ordered by date, as a line chart `width`
+/// characters wide, and `height` tall, with the rates on the left, and the
+/// dates below. Lines to, or from interpolated rates are dotted, and stretches
+/// of the x axis without any published rate are marked with `┄`.
+pub fn braille(series: &[DatedRate], width: usize, height: usize) -> Vec<String> {
+    let (Some(first), Some(last)) = (series.first(), series.last()) else {
+        return Vec::new();
+    };
+    let (width, height) = (width.max(2), height.max(2));
+    let min = series
+        .iter()
+        .map(|rate| rate.rate)
+        .min()
+        .unwrap_or_default();
+    let max = series
+        .iter()
+        .map(|rate| rate.rate)
+        .max()
+        .unwrap_or_default();
+
+    let days = (last.date - first.date).num_days().max(1) as f64;
+    let x_of = |date: NaiveDate| {
+        let offset = (date - first.date).num_days() as f64;
+        (offset / days * (width * 2 - 1) as f64).round() as usize
+    };
+    let y_of = |rate: Decimal| {
+        let range = (max - min).to_f64().unwrap_or_default();
+        let dots = (height * 4 - 1) as f64;
+        match range > 0.0 {
+            true => ((max - rate).to_f64().unwrap_or_default() / range * dots).round() as usize,
+            false => (dots / 2.0) as usize,
+        }
+    };
+
+    let mut canvas = Canvas::new(width, height);
+    let mut published = vec![false; width];
+    let mut plotted = vec![false; width];
+    let mut previous: Option<(usize, usize, RateStatus)> = None;
+    for rate in series {
+        let (x, y) = (x_of(rate.date), y_of(rate.rate));
+        match previous {
+            Some((previous_x, previous_y, status)) => canvas.line(
+                (previous_x, previous_y),
+                (x, y),
+                status != RateStatus::Observed || rate.status != RateStatus::Observed,
+            ),
+            None => canvas.set(x, y),
+        }
+
+        plotted[x / 2] = true;
+        published[x / 2] |= rate.status == RateStatus::Observed;
+        previous = Some((x, y, rate.status));
+    }
+
+    let labels = [
+        max.round_dp(4),
+        ((max + min) / Decimal::TWO).round_dp(4),
+        min.round_dp(4),
+    ]
+    .map(|rate| rate.to_string());
+    let label_width = labels.iter().map(String::len).max().unwrap_or_default();
+    let label_of = |row: usize| match row {
+        0 => Some(&labels[0]),
+        row if row == height / 2 && height > 2 => Some(&labels[1]),
+        row if row == height - 1 => Some(&labels[2]),
+        _ => None,
+    };
+
+    let mut lines: Vec<String> = canvas
+        .rows()
+        .into_iter()
+        .enumerate()
+        .map(|(row, cells)| match label_of(row) {
+            Some(label) => format!("{label:>label_width$} ┤{cells}"),
+            None => format!("{:label_width$} │{cells}", ""),
+        })
+        .collect();
+
+    let axis: String = (0..width)
+        .map(|column| match plotted[column] && !published[column] {
+            true => '┄',
+            false => '─',
+        })
+        .collect();
+    lines.push(format!("{:label_width$} └{axis}", ""));
+
+    let (start, end) = (first.date.to_string(), last.date.to_string());
+    let gap = width.saturating_sub(start.len() + end.len()).max(1);
+    lines.push(format!("{:label_width$}  {start}{:gap$}{end}", "", ""));
+
+    if plotted
+        .iter()
+        .zip(&published)
+        .any(|(plotted, published)| *plotted && !published)
+    {
+        lines.push(format!("{:label_width$}  ┄ interpolated rates only", ""));
+    }
+
+    lines
+}
+
+/// Size of exported charts in pixels
+const SVG_WIDTH: f64 = 960.0;
+const SVG_HEIGHT: f64 = 540.0;
+
+/// Edges of the plotting area of exported charts, in pixels
+const PLOT_LEFT: f64 = 80.0;
+const PLOT_RIGHT: f64 = 936.0;
+const PLOT_TOP: f64 = 64.0;
+const PLOT_BOTTOM: f64 = 448.0;
+
+/// Colors of the pairs on exported charts, in order
+const COLORS: [&str; 6] = [
+    "#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b",
+];
+
+/// Only interpolated stretches longer than a weekend are shaded, or every
+/// chart longer than a few months would be grey
+const SHADED_INTERPOLATED_DAYS: u64 = 3;
+
+/// The rates of a currency pair on an exported chart
+pub struct Line<'a> {
+    pub label: String,
+    pub rates: &'a [DatedRate],
+}
+
+/// Dates on an exported chart that aren't backed by published rates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Span {
+    /// Some pair has no rate at all
+    Gap,
+    /// Every pair has a rate, but some are interpolated
+    Interpolated,
+}
+
+/// Stretches of dates from `start` to `end` where some line has no rate, or
+/// only an interpolated one
+fn spans(lines: &[Line], start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, NaiveDate, Span)> {
+    let mut spans: Vec<(NaiveDate, NaiveDate, Span)> = Vec::new();
+
+    for date in start.iter_days().take_while(|date| *date <= end) {
+        let statuses: Vec<Option<RateStatus>> = lines
+            .iter()
+            .map(|line| {
+                line.rates
+                    .binary_search_by_key(&date, |rate| rate.date)
+                    .ok()
+                    .map(|index| line.rates[index].status)
+            })
+            .collect();
+        let span = if statuses
+            .iter()
+            .any(|status| matches!(status, None | Some(RateStatus::Missing)))
+        {
+            Span::Gap
+        } else if statuses.contains(&Some(RateStatus::Interpolated)) {
+            Span::Interpolated
+        } else {
+            continue;
+        };
+
+        match spans.last_mut() {
+            Some((_, last, previous)) if *previous == span && last.succ_opt() == Some(date) => {
+                *last = date
+            }
+            _ => spans.push((date, date, span)),
+        }
+    }
+
+    spans.retain(|(first, last, span)| {
+        *span == Span::Gap || (*last - *first).num_days() as u64 + 1 >= SHADED_INTERPOLATED_DAYS
+    });
+    spans
+}
+
+/// A step between ticks of about `range / ticks` that's 1, 2, 2.5, or 5 times
+/// a power of 10
+fn nice_step(range: f64, ticks: usize) -> f64 {
+    let raw = range / ticks as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+
+    [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .map(|multiple| multiple * magnitude)
+        .find(|step| *step >= raw)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// How many decimal places it takes to write multiples of `step`
+fn decimals(step: f64) -> usize {
+    (0..10)
+        .find(|decimals| {
+            let scaled = step * 10f64.powi(*decimals as i32);
+            (scaled - scaled.round()).abs() < 1e-6
+        })
+        .unwrap_or(10)
+}
+
+/// Escapes text for SVG
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Draws `lines`, each ordered by date, as an SVG line chart from `start` to
+/// `end` with axes, and a legend. Lines to, or from interpolated rates are
+/// dashed, stretches of interpolated rates longer than a weekend are shaded,
+/// and dates without any rate are hatched.
+pub fn svg(title: &str, lines: &[Line], start: NaiveDate, end: NaiveDate) -> String {
+    let rates = || {
+        lines
+            .iter()
+            .flat_map(|line| line.rates)
+            .map(|rate| rate.rate)
+    };
+    let min = rates()
+        .min()
+        .unwrap_or_default()
+        .to_f64()
+        .unwrap_or_default();
+    let max = rates()
+        .max()
+        .unwrap_or_default()
+        .to_f64()
+        .unwrap_or_default();
+    let range = match max - min {
+        range if range > 0.0 => range,
+        _ => max.abs().max(1.0) / 100.0,
+    };
+    let step = nice_step(range, 5);
+    let (low, high) = ((min / step).floor() * step, (max / step).ceil() * step);
+    let (low, high) = match high > low {
+        true => (low, high),
+        false => (low - step, high + step),
+    };
+
+    let days = (end - start).num_days().max(1) as f64;
+    let x_of = |date: NaiveDate| {
+        PLOT_LEFT + (date - start).num_days() as f64 / days * (PLOT_RIGHT - PLOT_LEFT)
+    };
+    let y_of = |rate: f64| PLOT_BOTTOM - (rate - low) / (high - low) * (PLOT_BOTTOM - PLOT_TOP);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{SVG_WIDTH}" height="{SVG_HEIGHT}" viewBox="0 0 {SVG_WIDTH} {SVG_HEIGHT}" font-family="DejaVu Sans, Helvetica, Arial, sans-serif" font-size="12">"##
+    );
+    let _ = writeln!(
+        svg,
+        r##"<defs><pattern id="gap" width="6" height="6" patternUnits="userSpaceOnUse" patternTransform="rotate(45)"><rect width="6" height="6" fill="#fbeaea"/><line x1="0" y1="0" x2="0" y2="6" stroke="#e3a3a3" stroke-width="2"/></pattern></defs>"##
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect width="{SVG_WIDTH}" height="{SVG_HEIGHT}" fill="#ffffff"/>"##
+    );
+    let _ = writeln!(
+        svg,
+        r##"<text x="{PLOT_LEFT}" y="28" font-size="16" font-weight="bold">{}</text>"##,
+        escape(title)
+    );
+    let _ = writeln!(
+        svg,
+        r##"<text x="{PLOT_LEFT}" y="48" fill="#555555">{start} to {end}</text>"##
+    );
+
+    // Gaps, and interpolated stretches go under everything else
+    let spans = spans(lines, start, end);
+    for (first, last, span) in &spans {
+        let left = (x_of(*first) - 0.5 / days * (PLOT_RIGHT - PLOT_LEFT)).max(PLOT_LEFT);
+        let right = (x_of(*last) + 0.5 / days * (PLOT_RIGHT - PLOT_LEFT)).min(PLOT_RIGHT);
+        let fill = match span {
+            Span::Gap => r##"fill="url(#gap)""##,
+            Span::Interpolated => r##"fill="#000000" fill-opacity="0.07""##,
+        };
+        let _ = writeln!(
+            svg,
+            r##"<rect x="{left:.1}" y="{PLOT_TOP}" width="{:.1}" height="{}" {fill}/>"##,
+            right - left,
+            PLOT_BOTTOM - PLOT_TOP
+        );
+        if *span == Span::Gap && right - left >= 48.0 {
+            let _ = writeln!(
+                svg,
+                r##"<text x="{:.1}" y="{}" text-anchor="middle" fill="#b04040">no data</text>"##,
+                (left + right) / 2.0,
+                PLOT_TOP + 16.0
+            );
+        }
+    }
+
+    // Grid, and labels of the y axis
+    let decimals = decimals(step);
+    let ticks = ((high - low) / step).round() as usize;
+    for tick in 0..=ticks {
+        let rate = low + step * tick as f64;
+        let y = y_of(rate);
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{PLOT_LEFT}" y1="{y:.1}" x2="{PLOT_RIGHT}" y2="{y:.1}" stroke="#e5e5e5"/>"##
+        );
+        let _ = writeln!(
+            svg,
+            r##"<text x="{}" y="{:.1}" text-anchor="end">{rate:.decimals$}</text>"##,
+            PLOT_LEFT - 8.0,
+            y + 4.0
+        );
+    }
+
+    // Ticks, and labels of the x axis
+    for tick in 0..=5 {
+        let date = start + Days::new((days * tick as f64 / 5.0).round() as u64);
+        let x = x_of(date);
+        let anchor = match tick {
+            0 => "start",
+            5 => "end",
+            _ => "middle",
+        };
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{x:.1}" y1="{PLOT_BOTTOM}" x2="{x:.1}" y2="{}" stroke="#333333"/>"##,
+            PLOT_BOTTOM + 5.0
+        );
+        let _ = writeln!(
+            svg,
+            r##"<text x="{x:.1}" y="{}" text-anchor="{anchor}">{date}</text>"##,
+            PLOT_BOTTOM + 20.0
+        );
+    }
+    let _ = writeln!(
+        svg,
+        r##"<path d="M{PLOT_LEFT},{PLOT_TOP} V{PLOT_BOTTOM} H{PLOT_RIGHT}" fill="none" stroke="#333333"/>"##
+    );
+
+    // The lines themselves. Dates without a rate aren't bridged.
+    for (index, line) in lines.iter().enumerate() {
+        let color = COLORS[index % COLORS.len()];
+        let (mut solid, mut dashed) = (String::new(), String::new());
+        let mut previous: Option<&DatedRate> = None;
+        let mut ends: [Option<NaiveDate>; 2] = [None, None];
+
+        for rate in line
+            .rates
+            .iter()
+            .filter(|rate| rate.status != RateStatus::Missing)
+        {
+            if let Some(previous) =
+                previous.filter(|previous| previous.date.succ_opt() == Some(rate.date))
+            {
+                let interpolated =
+                    previous.status != RateStatus::Observed || rate.status != RateStatus::Observed;
+                let (path, end) = match interpolated {
+                    false => (&mut solid, &mut ends[0]),
+                    true => (&mut dashed, &mut ends[1]),
+                };
+                let point = |rate: &DatedRate| {
+                    format!(
+                        "{:.1},{:.1}",
+                        x_of(rate.date),
+                        y_of(rate.rate.to_f64().unwrap_or_default())
+                    )
+                };
+                if *end != Some(previous.date) {
+                    let _ = write!(path, "M{} ", point(previous));
+                }
+                let _ = write!(path, "L{} ", point(rate));
+                *end = Some(rate.date);
+            }
+            previous = Some(rate);
+        }
+
+        for (path, dash) in [(solid, ""), (dashed, r#" stroke-dasharray="4 3""#)] {
+            if !path.is_empty() {
+                let _ = writeln!(
+                    svg,
+                    r##"<path d="{}" fill="none" stroke="{color}" stroke-width="1.5"{dash}/>"##,
+                    path.trim_end()
+                );
+            }
+        }
+    }
+
+    // Legend, below the x axis. Samples are drawn around their own origin.
+    let line_sample = |color: &str, dash: &str| {
+        format!(r##"<line x1="0" y1="0" x2="20" y2="0" stroke="{color}" stroke-width="2"{dash}/>"##)
+    };
+    let mut legend: Vec<(String, &str)> = lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            (
+                line_sample(COLORS[index % COLORS.len()], ""),
+                line.label.as_str(),
+            )
+        })
+        .collect();
+    legend.push((
+        line_sample("#555555", r#" stroke-dasharray="4 3""#),
+        "interpolated",
+    ));
+    if spans.iter().any(|(_, _, span)| *span == Span::Interpolated) {
+        legend.push((
+            r##"<rect x="0" y="-6" width="20" height="12" fill="#000000" fill-opacity="0.07"/>"##
+                .to_string(),
+            "interpolated for 3+ days",
+        ));
+    }
+    if spans.iter().any(|(_, _, span)| *span == Span::Gap) {
+        legend.push((
+            r##"<rect x="0" y="-6" width="20" height="12" fill="url(#gap)"/>"##.to_string(),
+            "no data",
+        ));
+    }
+
+    let mut x = PLOT_LEFT;
+    for (sample, label) in legend {
+        let _ = writeln!(
+            svg,
+            r##"<g transform="translate({x:.1},{})">{sample}<text x="28" y="4">{}</text></g>"##,
+            SVG_HEIGHT - 36.0,
+            escape(label)
+        );
+        x += 52.0 + 6.5 * label.chars().count() as f64;
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Rescales `series` so that its first rate is 100, e.g to compare pairs of
+/// very different magnitudes on one chart
+pub fn rebase(series: &mut [DatedRate]) {
+    let Some(first) = series
+        .first()
+        .map(|rate| rate.rate)
+        .filter(|rate| !rate.is_zero())
+    else {
+        return;
+    };
+
+    for rate in series {
+        rate.rate = (rate.rate * Decimal::ONE_HUNDRED / first).round_dp(6);
+    }
+}
+
+/// Parses `--out` for clap. Only `.svg`, and `.png` files can be written.
+pub fn parse_out_arg(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+
+    match extension.as_deref() {
+        Some("svg") => Ok(path),
+        Some("png") if cfg!(feature = "png") => Ok(path),
+        Some("png") => {
+            Err("this build of moneyman can't write PNG, so use .svg instead".to_string())
+        }
+        _ => Err(format!("expected a .svg, or .png file, got {path:?}")),
+    }
+}
+
+/// Writes an SVG chart to `path`, as is, or as PNG if it ends with `.png`
+pub fn write(path: &Path, svg: &str) -> Result<(), CliError> {
+    let is_png = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+
+    match is_png {
+        true => std::fs::write(path, png(svg)?)?,
+        false => std::fs::write(path, svg)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "png")]
+fn png(svg: &str) -> Result<Vec<u8>, CliError> {
+    use resvg::{tiny_skia, usvg};
+
+    let failed = |error: String| CliError::Io(std::io::Error::other(error));
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|error| failed(error.to_string()))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| failed("the chart has no size".to_string()))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    pixmap
+        .encode_png()
+        .map_err(|error| failed(error.to_string()))
+}
+
+#[cfg(not(feature = "png"))]
+fn png(_svg: &str) -> Result<Vec<u8>, CliError> {
+    Err(CliError::Io(std::io::Error::other(
+        "this build of moneyman can't write PNG",
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_draws_lines_of_braille_dots() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.line((0, 3), (3, 0), false);
+        assert_eq!(vec!["⡠⠊"], canvas.rows());
+
+        let mut canvas = Canvas::new(2, 1);
+        canvas.line((0, 0), (3, 0), true);
+        assert_eq!(vec!["⠁⠁"], canvas.rows());
+    }
+
+    #[test]
+    fn it_finds_gaps_and_long_interpolated_spans() {
+        let date = |date: &str| date.parse::<NaiveDate>().unwrap();
+        let rate = |day: &str, status| DatedRate {
+            date: date(day),
+            rate: Decimal::ONE,
+            status,
+        };
+        let rates = [
+            rate("2023-04-27", RateStatus::Observed),
+            rate("2023-04-28", RateStatus::Observed),
+            rate("2023-04-29", RateStatus::Interpolated),
+            rate("2023-04-30", RateStatus::Interpolated),
+            rate("2023-05-01", RateStatus::Interpolated),
+            rate("2023-05-02", RateStatus::Observed),
+            rate("2023-05-03", RateStatus::Observed),
+            rate("2023-05-06", RateStatus::Interpolated),
+            rate("2023-05-07", RateStatus::Interpolated),
+            rate("2023-05-08", RateStatus::Observed),
+        ];
+        let lines = [Line {
+            label: "EUR -> USD".to_string(),
+            rates: &rates,
+        }];
+
+        assert_eq!(
+            vec![
+                (date("2023-04-26"), date("2023-04-26"), Span::Gap),
+                (date("2023-04-29"), date("2023-05-01"), Span::Interpolated),
+                (date("2023-05-04"), date("2023-05-05"), Span::Gap),
+                (date("2023-05-09"), date("2023-05-10"), Span::Gap),
+            ],
+            spans(&lines, date("2023-04-26"), date("2023-05-10"))
+        );
+    }
+
+    #[test]
+    fn it_picks_nice_ticks() {
+        assert_eq!(0.01, nice_step(0.0482, 5));
+        assert_eq!(2.5, nice_step(11.0, 5));
+        assert_eq!(500.0, nice_step(2000.0, 5));
+        assert_eq!(
+            (0, 2, 3),
+            (decimals(500.0), decimals(0.01), decimals(0.025))
+        );
+    }
+
+    #[test]
+    fn it_rebases_series_to_100() {
+        let mut series = [Decimal::TWO, Decimal::ONE, Decimal::from(3)].map(|rate| DatedRate {
+            date: NaiveDate::MIN,
+            rate,
+            status: RateStatus::Observed,
+        });
+        rebase(&mut series);
+
+        assert_eq!(
+            vec![Decimal::ONE_HUNDRED, Decimal::from(50), Decimal::from(150)],
+            series.iter().map(|rate| rate.rate).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_only_writes_svg_or_png() {
+        assert!(parse_out_arg("rates.SVG").is_ok());
+        assert_eq!(cfg!(feature = "png"), parse_out_arg("rates.png").is_ok());
+        assert!(parse_out_arg("rates.pdf").is_err());
+        assert!(parse_out_arg("rates").is_err());
+    }
+}
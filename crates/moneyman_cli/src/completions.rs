@@ -0,0 +1,158 @@
+use std::{io::Write, path::Path};
+
+use clap::{Arg, Command, CommandFactory};
+use clap_complete::{
+    engine::{ArgValueCandidates, CompletionCandidate},
+    env::Shells,
+    Shell,
+};
+use moneyman::ExchangeStore;
+use rusty_money::iso;
+
+use crate::{
+    config::{self, Config},
+    currency::Currency,
+    error::CliError,
+    Cli,
+};
+
+/// The environment variable the completion scripts set to ask for
+/// completions
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Whether the argument takes a currency, e.g `--from`
+fn takes_currency(arg: &Arg) -> bool {
+    arg.get_value_names()
+        .is_some_and(|names| names.iter().any(|name| name == "CURRENCY"))
+}
+
+/// Completes every argument that takes a currency with `candidates`
+fn complete_currencies(cmd: Command, candidates: &ArgValueCandidates) -> Command {
+    let subcommands: Vec<String> = cmd
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    let cmd = cmd.mut_args(|arg| match takes_currency(&arg) {
+        true => arg.add(candidates.clone()),
+        false => arg,
+    });
+
+    subcommands.iter().fold(cmd, |cmd, name| {
+        cmd.mut_subcommand(name, |subcommand| {
+            complete_currencies(subcommand, candidates)
+        })
+    })
+}
+
+/// The CLI, with currencies completed from the local data store when the
+/// completion is asked for, so they're as fresh as the store
+pub fn command() -> Command {
+    let candidates = ArgValueCandidates::new(|| {
+        let data_dir = Config::load(&config::path()).unwrap_or_default().data_dir();
+
+        store_currencies(&data_dir)
+            .into_iter()
+            .map(CompletionCandidate::new)
+            .collect::<Vec<_>>()
+    });
+
+    complete_currencies(Cli::command(), &candidates)
+}
+
+/// ISO alpha codes of the currencies in the local data store. Empty if it
+/// hasn't been synced yet, since completing shouldn't download anything.
+fn store_currencies(data_dir: &Path) -> Vec<&'static str> {
+    if !data_dir.join("eurofxref-hist.db3").exists() {
+        return Vec::new();
+    }
+
+    let Ok(stats) = ExchangeStore::open(data_dir.to_path_buf()).and_then(|store| store.stats())
+    else {
+        return Vec::new();
+    };
+
+    let mut currencies: Vec<&'static str> = stats
+        .currencies
+        .iter()
+        .filter_map(|coverage| Currency::find(&coverage.currency))
+        .map(|currency| currency.0.iso_alpha_code)
+        .chain([iso::EUR.iso_alpha_code])
+        .collect();
+    currencies.sort();
+
+    currencies
+}
+
+/// Writes the completion script for `shell`. The script calls `moneyman`
+/// back with `COMPLETE` set whenever something is completed.
+pub fn generate(shell: Shell, writer: &mut dyn Write) -> Result<(), CliError> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .ok_or_else(|| CliError::Io(std::io::Error::other(format!("{shell} isn't supported"))))?;
+    let name = Cli::command().get_name().to_string();
+
+    completer.write_registration(COMPLETE_VAR, &name, &name, &name, writer)?;
+
+    Ok(())
+}
+
+/// Writes the man page of `cmd` to `writer`, or a page per subcommand to
+/// `out_dir` if given.
+pub fn man(cmd: Command, out_dir: Option<&Path>, writer: &mut dyn Write) -> Result<(), CliError> {
+    match out_dir {
+        Some(out_dir) => std::fs::create_dir_all(out_dir)
+            .and_then(|_| clap_mangen::generate_to(cmd, out_dir))
+            .map_err(CliError::Io),
+        None => clap_mangen::Man::new(cmd)
+            .render(writer)
+            .map_err(CliError::Io),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_completes_currencies_of_every_subcommand() {
+        let candidates = ArgValueCandidates::new(|| {
+            vec![
+                CompletionCandidate::new("EUR"),
+                CompletionCandidate::new("JPY"),
+            ]
+        });
+        let mut cmd = complete_currencies(Cli::command(), &candidates);
+        let args = ["moneyman", "convert", "100", "--to", "J"].map(std::ffi::OsString::from);
+
+        let completed: Vec<String> =
+            clap_complete::engine::complete(&mut cmd, args.to_vec(), 4, None)
+                .unwrap()
+                .iter()
+                .map(|candidate| candidate.get_value().to_string_lossy().to_string())
+                .collect();
+        assert_eq!(vec!["JPY"], completed);
+    }
+
+    #[test]
+    fn it_writes_scripts_that_complete_dynamically() {
+        let mut script = Vec::new();
+
+        generate(Shell::Bash, &mut script).unwrap();
+
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("COMPLETE=\"bash\""));
+        assert!(script.contains("moneyman"));
+    }
+
+    #[test]
+    fn it_renders_a_man_page() {
+        let mut page = Vec::new();
+
+        man(Cli::command(), None, &mut page).unwrap();
+
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.starts_with(".ie"));
+        assert!(page.contains("convert\\-file"));
+    }
+}
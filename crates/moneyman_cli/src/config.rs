@@ -0,0 +1,620 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use moneyman::{Rounding, RoundingMode, SyncOptions, SyncPolicy};
+use rusty_money::iso;
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+use crate::{
+    currency::Currency,
+    error::CliError,
+    locale::{parse_locale_arg, Locale},
+    output::OutputFormat,
+};
+
+/// Every key `moneyman config` knows about, in the order `list` shows them
+pub const KEYS: [&str; 16] = [
+    "to",
+    "fallback",
+    "output",
+    "locale",
+    "decimal_places",
+    "round",
+    "data_dir",
+    "auto_sync",
+    "stale_after_days",
+    "sync_interval_hours",
+    "http.base_url",
+    "http.proxy",
+    "http.timeout",
+    "http.retries",
+    "http.user_agent",
+    "http.ca_bundle",
+];
+
+/// Keys in the `[http.headers]` table start with this, e.g
+/// `http.headers.X-Api-Key`
+const HEADER_PREFIX: &str = "http.headers.";
+
+/// Keys with integer values. The rest are strings.
+const INTEGER_KEYS: [&str; 5] = [
+    "decimal_places",
+    "stale_after_days",
+    "sync_interval_hours",
+    "http.timeout",
+    "http.retries",
+];
+
+/// How to deal with dates without rates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FallbackStrategy {
+    /// Fail unless `--fallback` is given
+    #[default]
+    None,
+    /// Interpolate missing rates from the neighboring dates, unless
+    /// `--no-fallback` is given
+    Linear,
+}
+
+/// When to sync the local data store without being asked to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoSync {
+    /// Only if it has never been synced
+    #[default]
+    Missing,
+    /// If it has never been synced, or its latest date is more than
+    /// `stale_after_days` business days old
+    Stale,
+    /// Never. Commands fail until `moneyman sync` is run.
+    Never,
+}
+
+/// The `[http]` table, used whenever moneyman downloads the ECB history.
+/// `moneyman sync` flags take precedence.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    pub base_url: Option<String>,
+    pub proxy: Option<String>,
+    /// In seconds
+    pub timeout: Option<u64>,
+    pub retries: Option<u32>,
+    pub user_agent: Option<String>,
+    pub ca_bundle: Option<PathBuf>,
+    /// Sent along with every request, unless `--header` gives one with the
+    /// same name
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Defaults for the CLI, read from `config.toml`. Flags always take
+/// precedence.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// ISO alpha code of the default target currency
+    pub to: Option<String>,
+    pub fallback: FallbackStrategy,
+    pub output: Option<OutputFormat>,
+    /// How `output = "human"` writes amounts, e.g `de-DE`
+    pub locale: Option<String>,
+    /// Decimal places to round converted amounts to
+    pub decimal_places: Option<u32>,
+    /// Name of the rounding mode, e.g `half-even`. See `RoundingMode`.
+    pub round: Option<String>,
+    /// Where the local data store is kept. Default: ~/.moneyman
+    pub data_dir: Option<PathBuf>,
+    pub auto_sync: AutoSync,
+    /// Business days the latest date may be behind today before
+    /// `auto_sync = "stale"` syncs. Default: 1
+    pub stale_after_days: Option<u32>,
+    /// Hours to wait between automatic sync attempts. Default: 1
+    pub sync_interval_hours: Option<u64>,
+    pub http: HttpConfig,
+}
+
+/// `$MONEYMAN_CONFIG` if set, or `$XDG_CONFIG_HOME/moneyman/config.toml`,
+/// which defaults to `~/.config/moneyman/config.toml`.
+pub fn path() -> PathBuf {
+    let non_empty = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+
+    if let Some(path) = non_empty("MONEYMAN_CONFIG") {
+        return PathBuf::from(path);
+    }
+
+    non_empty("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home_dir| home_dir.join(".config")))
+        .expect("need a home directory")
+        .join("moneyman")
+        .join("config.toml")
+}
+
+/// `hours` as a `Duration`, unless it's too many seconds for a `u64`
+fn interval(hours: u64) -> Option<Duration> {
+    hours.checked_mul(60 * 60).map(Duration::from_secs)
+}
+
+fn invalid(path: &Path, reason: impl std::fmt::Display) -> CliError {
+    CliError::Config(format!("{}: {reason}", path.display()))
+}
+
+impl Config {
+    /// Parses, and validates a config
+    fn parse(content: &str, path: &Path) -> Result<Config, CliError> {
+        let config: Config = toml::from_str(content).map_err(|err| invalid(path, err.message()))?;
+
+        if let Some(to) = &config.to {
+            Currency::find(to).ok_or_else(|| invalid(path, format!("unknown currency {to:?}")))?;
+        }
+
+        if let Some(locale) = &config.locale {
+            parse_locale_arg(locale).map_err(|reason| invalid(path, reason))?;
+        }
+
+        if let Some(hours) = config.sync_interval_hours {
+            interval(hours).ok_or_else(|| {
+                invalid(path, format!("sync_interval_hours {hours} is too large"))
+            })?;
+        }
+
+        if let Some(round) = &config.round {
+            RoundingMode::from_name(round)
+                .ok_or_else(|| invalid(path, format!("unknown rounding mode {round:?}")))?;
+        }
+
+        Ok(config)
+    }
+
+    /// Reads the config at `path`. A missing file is the same as an empty
+    /// one.
+    pub fn load(path: &Path) -> Result<Config, CliError> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Config::parse(&content, path),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(CliError::Io(err)),
+        }
+    }
+
+    /// The default target currency
+    pub fn to(&self) -> Option<iso::Currency> {
+        self.to
+            .as_deref()
+            .and_then(Currency::find)
+            .map(|currency| currency.0)
+    }
+
+    pub fn locale(&self) -> Option<Locale> {
+        self.locale.as_deref().and_then(Locale::find)
+    }
+
+    pub fn data_dir(&self) -> PathBuf {
+        self.data_dir.clone().unwrap_or_else(|| {
+            dirs::home_dir()
+                .map(|home_dir| home_dir.join(".moneyman"))
+                .expect("need a home directory")
+        })
+    }
+
+    /// Whether missing rates are interpolated, unless a flag says otherwise
+    pub fn fallback(&self, fallback: bool, no_fallback: bool) -> bool {
+        match (fallback, no_fallback) {
+            (true, _) => true,
+            (_, true) => false,
+            _ => self.fallback == FallbackStrategy::Linear,
+        }
+    }
+
+    /// How converted amounts are rounded, unless a flag says otherwise. With
+    /// only a precision, rounds half to even.
+    pub fn rounding(&self, mode: Option<RoundingMode>, precision: Option<u32>) -> Rounding {
+        let precision = precision.or(self.decimal_places);
+        let mode = mode
+            .or_else(|| self.round.as_deref().and_then(RoundingMode::from_name))
+            .unwrap_or(match precision {
+                Some(_) => RoundingMode::HalfEven,
+                None => RoundingMode::Exact,
+            });
+
+        Rounding { mode, precision }
+    }
+
+    /// When to sync before opening the store. `offline` never touches the
+    /// network, whatever `auto_sync` says.
+    pub fn sync_policy(&self, offline: bool) -> SyncPolicy {
+        let defaults = SyncPolicy::default();
+
+        SyncPolicy {
+            max_business_days_behind: match self.auto_sync {
+                AutoSync::Stale => Some(self.stale_after_days.unwrap_or(1)),
+                AutoSync::Missing | AutoSync::Never => None,
+            },
+            min_attempt_interval: self
+                .sync_interval_hours
+                .and_then(interval)
+                .unwrap_or(defaults.min_attempt_interval),
+            offline: offline || self.auto_sync == AutoSync::Never,
+        }
+    }
+
+    /// `SyncOptions::default()` with the `[http]` table applied
+    pub fn sync_options(&self) -> SyncOptions {
+        let defaults = SyncOptions::default();
+        let http = self.http.clone();
+
+        SyncOptions {
+            base_url: http.base_url.unwrap_or(defaults.base_url),
+            proxy: http.proxy.or(defaults.proxy),
+            timeout: http.timeout.map(Duration::from_secs).or(defaults.timeout),
+            retries: http.retries.unwrap_or(defaults.retries),
+            user_agent: http.user_agent.or(defaults.user_agent),
+            ca_bundle: http.ca_bundle.or(defaults.ca_bundle),
+            headers: http.headers.into_iter().collect(),
+            ..defaults
+        }
+    }
+}
+
+fn unknown_key(key: &str) -> CliError {
+    CliError::Config(format!(
+        "unknown key {key:?}. Known keys: {}, {HEADER_PREFIX}<NAME>",
+        KEYS.join(", ")
+    ))
+}
+
+fn is_known(key: &str) -> bool {
+    KEYS.contains(&key)
+        || key
+            .strip_prefix(HEADER_PREFIX)
+            .is_some_and(|name| !name.is_empty())
+}
+
+/// The tables a dotted key is in, followed by its name, e.g `http.proxy` is
+/// `["http", "proxy"]`. Header names may have dots of their own.
+fn key_path(key: &str) -> Vec<&str> {
+    match key.strip_prefix(HEADER_PREFIX) {
+        Some(name) => vec!["http", "headers", name],
+        None => key.split('.').collect(),
+    }
+}
+
+/// Reads the config as an editable document, keeping comments
+fn read_document(path: &Path) -> Result<DocumentMut, CliError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(CliError::Io(err)),
+    };
+
+    content
+        .parse::<DocumentMut>()
+        .map_err(|err| invalid(path, err))
+}
+
+/// Finds the table with the given names, e.g `["http", "headers"]`
+fn lookup_table<'d>(
+    document: &'d DocumentMut,
+    names: &[&str],
+) -> Option<&'d dyn toml_edit::TableLike> {
+    names.iter().try_fold(
+        document.as_table() as &dyn toml_edit::TableLike,
+        |table, name| table.get(name)?.as_table_like(),
+    )
+}
+
+/// Finds the value of a dotted key, e.g `http.proxy`
+fn lookup<'d>(document: &'d DocumentMut, key: &str) -> Option<&'d Value> {
+    let path = key_path(key);
+    let (name, tables) = path.split_last()?;
+
+    lookup_table(document, tables)?.get(name)?.as_value()
+}
+
+/// Shows a value without TOML quotes
+fn display(value: &Value) -> String {
+    match value.as_str() {
+        Some(value) => value.to_string(),
+        None => value.clone().decorated("", "").to_string(),
+    }
+}
+
+/// The value of `key` in the config file, or `None` if it isn't set
+pub fn get(path: &Path, key: &str) -> Result<Option<String>, CliError> {
+    if !is_known(key) {
+        return Err(unknown_key(key));
+    }
+
+    Ok(lookup(&read_document(path)?, key).map(display))
+}
+
+/// Every known key, and its value in the config file, followed by the
+/// headers that are set
+pub fn list(path: &Path) -> Result<Vec<(String, Option<String>)>, CliError> {
+    let document = read_document(path)?;
+    let headers: Vec<String> = lookup_table(&document, &["http", "headers"])
+        .map(|headers| {
+            headers
+                .iter()
+                .map(|(name, _)| format!("{HEADER_PREFIX}{name}"))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(KEYS
+        .iter()
+        .map(|key| key.to_string())
+        .chain(headers)
+        .map(|key| {
+            let value = lookup(&document, &key).map(display);
+            (key, value)
+        })
+        .collect())
+}
+
+/// Puts `value` at a dotted key, e.g `http.proxy`, creating its tables if
+/// needed
+fn insert(
+    document: &mut DocumentMut,
+    key: &str,
+    value: Value,
+    path: &Path,
+) -> Result<(), CliError> {
+    let key_path = key_path(key);
+    let (name, tables) = key_path.split_last().expect("keys aren't empty");
+
+    let table = tables
+        .iter()
+        .try_fold(document.as_table_mut(), |table, name| {
+            table
+                .entry(name)
+                .or_insert_with(|| Item::Table(Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| invalid(path, format!("{name} is not a table")))
+        })?;
+    table[name] = Item::Value(value);
+
+    Ok(())
+}
+
+/// Sets `key` in the config file, creating it if needed. The value is
+/// validated before anything is written. The rest of the file isn't, so a
+/// config that fails to load can be fixed one key at a time.
+pub fn set(path: &Path, key: &str, value: &str) -> Result<(), CliError> {
+    if !is_known(key) {
+        return Err(unknown_key(key));
+    }
+
+    let value = match INTEGER_KEYS.contains(&key) {
+        true => Value::from(
+            value
+                .parse::<i64>()
+                .map_err(|_| CliError::Config(format!("{key} must be a whole number")))?,
+        ),
+        false => Value::from(value),
+    };
+
+    let mut alone = DocumentMut::new();
+    insert(&mut alone, key, value.clone(), path)?;
+    Config::parse(&alone.to_string(), path)?;
+
+    let mut document = read_document(path)?;
+    insert(&mut document, key, value, path)?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(path, document.to_string()).map_err(CliError::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "moneyman-config-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+
+        dir.join("config.toml")
+    }
+
+    #[test]
+    fn it_reads_defaults_from_the_config() {
+        let config = Config::parse(
+            r#"
+                to = "eur"
+                fallback = "linear"
+                output = "json"
+                decimal_places = 2
+
+                [http]
+                proxy = "http://proxy.corp:3128"
+                timeout = 5
+            "#,
+            Path::new("config.toml"),
+        )
+        .unwrap();
+
+        assert_eq!(Some(*iso::EUR), config.to());
+        assert!(config.fallback(false, false));
+        assert!(!config.fallback(false, true));
+        assert_eq!(Some(OutputFormat::Json), config.output);
+
+        let options = config.sync_options();
+        assert_eq!(Some("http://proxy.corp:3128".to_string()), options.proxy);
+        assert_eq!(Some(Duration::from_secs(5)), options.timeout);
+        assert_eq!(SyncOptions::default().retries, options.retries);
+    }
+
+    #[test]
+    fn it_lets_flags_override_rounding() {
+        let config = Config {
+            decimal_places: Some(2),
+            ..Config::default()
+        };
+
+        assert_eq!(Rounding::default(), Config::default().rounding(None, None));
+        assert_eq!(
+            Rounding {
+                mode: RoundingMode::HalfEven,
+                precision: Some(2),
+            },
+            config.rounding(None, None)
+        );
+        assert_eq!(
+            Rounding {
+                mode: RoundingMode::Floor,
+                precision: Some(0),
+            },
+            config.rounding(Some(RoundingMode::Floor), Some(0))
+        );
+        assert_eq!(
+            Rounding::minor_units(RoundingMode::Ceiling),
+            Config {
+                round: Some("ceiling".to_string()),
+                ..Config::default()
+            }
+            .rounding(None, None)
+        );
+    }
+
+    #[test]
+    fn it_builds_sync_policies() {
+        let config = Config::parse(
+            "auto_sync = \"stale\"\nsync_interval_hours = 6",
+            Path::new("config.toml"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            SyncPolicy {
+                max_business_days_behind: Some(1),
+                min_attempt_interval: Duration::from_secs(6 * 60 * 60),
+                offline: false,
+            },
+            config.sync_policy(false)
+        );
+        assert!(config.sync_policy(true).offline);
+        assert_eq!(
+            SyncPolicy {
+                offline: true,
+                ..SyncPolicy::default()
+            },
+            Config {
+                auto_sync: AutoSync::Never,
+                ..Config::default()
+            }
+            .sync_policy(false)
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_configs() {
+        let parse = |content| Config::parse(content, Path::new("config.toml"));
+
+        assert!(matches!(parse("to = \"xxx\""), Err(CliError::Config(_))));
+        assert!(matches!(parse("colour = true"), Err(CliError::Config(_))));
+        assert!(matches!(parse("round = \"up\""), Err(CliError::Config(_))));
+        assert!(matches!(parse("locale = \"xx\""), Err(CliError::Config(_))));
+        assert!(matches!(
+            parse("sync_interval_hours = 9223372036854775807"),
+            Err(CliError::Config(_))
+        ));
+        assert!(matches!(
+            parse("fallback = \"cubic\""),
+            Err(CliError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn it_sets_and_gets_keys_keeping_comments() {
+        let path = temp_config();
+
+        set(&path, "to", "USD").unwrap();
+        std::fs::write(
+            &path,
+            format!(
+                "# Team defaults\n{}",
+                std::fs::read_to_string(&path).unwrap()
+            ),
+        )
+        .unwrap();
+        set(&path, "http.retries", "5").unwrap();
+
+        assert_eq!(Some("USD".to_string()), get(&path, "to").unwrap());
+        assert_eq!(Some("5".to_string()), get(&path, "http.retries").unwrap());
+        assert_eq!(None, get(&path, "output").unwrap());
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("# Team defaults"));
+        assert_eq!(Some(5), Config::load(&path).unwrap().http.retries);
+    }
+
+    #[test]
+    fn it_sets_keys_in_configs_that_fail_to_load() {
+        let path = temp_config();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "colour = true\nto = \"xxx\"\n").unwrap();
+        assert!(Config::load(&path).is_err());
+
+        set(&path, "to", "EUR").unwrap();
+
+        assert_eq!(Some("EUR".to_string()), get(&path, "to").unwrap());
+        assert!(list(&path)
+            .unwrap()
+            .contains(&("to".to_string(), Some("EUR".to_string()))));
+
+        std::fs::write(
+            &path,
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .replace("colour = true\n", ""),
+        )
+        .unwrap();
+        assert_eq!(Some(*iso::EUR), Config::load(&path).unwrap().to());
+    }
+
+    #[test]
+    fn it_sets_and_lists_headers() {
+        let path = temp_config();
+
+        set(&path, "http.headers.X-Api-Key", "secret").unwrap();
+        set(&path, "http.proxy", "http://proxy.corp:3128").unwrap();
+
+        assert_eq!(
+            Some("secret".to_string()),
+            get(&path, "http.headers.X-Api-Key").unwrap()
+        );
+        assert!(list(&path).unwrap().contains(&(
+            "http.headers.X-Api-Key".to_string(),
+            Some("secret".to_string())
+        )));
+        assert_eq!(
+            vec![("X-Api-Key".to_string(), "secret".to_string())],
+            Config::load(&path).unwrap().sync_options().headers
+        );
+        assert!(get(&path, "http.headers.").is_err());
+    }
+
+    #[test]
+    fn it_refuses_to_set_invalid_values() {
+        let path = temp_config();
+
+        assert!(set(&path, "output", "xml").is_err());
+        assert!(set(&path, "decimal_places", "two").is_err());
+        assert!(set(&path, "sync_interval_hours", &i64::MAX.to_string()).is_err());
+        assert!(set(&path, "colour", "red").is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,538 @@
+use std::io::{Read, Write};
+
+use chrono::NaiveDate;
+use moneyman::{ConversionError, ExchangeStore};
+use rust_decimal::Decimal;
+use rusty_money::{iso, Money};
+use serde_json::Value;
+
+use crate::{error::CliError, output::OutputFormat};
+
+/// Columns appended to every row of the input
+const APPENDED_COLUMNS: [&str; 6] = [
+    "converted_amount",
+    "converted_currency",
+    "rate",
+    "rate_date",
+    "provenance",
+    "error",
+];
+
+/// Where the rate of a converted row came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Provenance {
+    /// Published by ECB on that date
+    Observed,
+    /// Interpolated from the neighboring dates, with `--fallback`
+    Interpolated,
+    /// The row is already in the target currency
+    SameCurrency,
+}
+
+impl Provenance {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Provenance::Observed => "observed",
+            Provenance::Interpolated => "interpolated",
+            Provenance::SameCurrency => "same-currency",
+        }
+    }
+}
+
+/// A successfully converted row
+#[derive(Clone, Debug, PartialEq)]
+pub struct Converted {
+    pub amount: Decimal,
+    /// Units of the target currency per one unit of the row's currency
+    pub rate: Decimal,
+    pub provenance: Provenance,
+}
+
+/// Names of the input columns to read from
+#[derive(Clone, Debug)]
+pub struct Columns {
+    pub amount: String,
+    pub currency: String,
+    /// Rows are converted on the latest date if there's no date column
+    pub date: Option<String>,
+}
+
+/// A row that couldn't be converted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowFailure {
+    /// Line of the row in the input, counting the header
+    pub line: u64,
+    pub message: String,
+}
+
+/// How many rows went through, and why the others didn't
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub converted: usize,
+    pub failures: Vec<RowFailure>,
+}
+
+/// Converts `amount` of `currency` to `to` with the store. With `fallback`,
+/// dates without an observation use interpolated rates instead of failing.
+pub fn convert_with_store(
+    store: &ExchangeStore,
+    amount: Decimal,
+    currency: &iso::Currency,
+    to: &iso::Currency,
+    date: NaiveDate,
+    fallback: bool,
+) -> Result<Converted, ConversionError> {
+    if currency == to {
+        return Ok(Converted {
+            amount,
+            rate: Decimal::ONE,
+            provenance: Provenance::SameCurrency,
+        });
+    }
+
+    // The rate is what a single unit converts to, so each row only looks up
+    // the store once, or twice when it falls back.
+    let one = Money::from_decimal(Decimal::ONE, currency);
+    let (rate, provenance) = match store.convert_on_date(one.clone(), to, date) {
+        Ok(rate) => (rate, Provenance::Observed),
+        Err(ConversionError::NoExchangeRate(_)) if fallback => (
+            store.convert_on_date_with_fallback(one, to, date)?,
+            Provenance::Interpolated,
+        ),
+        Err(err) => return Err(err),
+    };
+    let rate = *rate.amount();
+
+    Ok(Converted {
+        amount: amount * rate,
+        rate,
+        provenance,
+    })
+}
+
+/// Where converted rows are written to
+enum Sink<W: Write> {
+    Delimited(Box<csv::Writer<W>>),
+    Json { writer: W, first: bool },
+}
+
+impl<W: Write> Sink<W> {
+    fn new(writer: W, format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Json => Sink::Json {
+                writer,
+                first: true,
+            },
+            OutputFormat::Tsv => Sink::Delimited(Box::new(
+                csv::WriterBuilder::new()
+                    .delimiter(b'\t')
+                    .from_writer(writer),
+            )),
+            OutputFormat::Plain | OutputFormat::Human | OutputFormat::Csv => {
+                Sink::Delimited(Box::new(csv::Writer::from_writer(writer)))
+            }
+        }
+    }
+
+    fn write(&mut self, headers: &[String], row: &[String]) -> Result<(), CliError> {
+        match self {
+            Sink::Delimited(writer) => writer.write_record(row).map_err(csv_to_io),
+            Sink::Json { writer, first } => {
+                // Written by hand rather than through a `serde_json::Map`, so
+                // the keys keep the order of the columns, and repeated
+                // headers aren't collapsed into one key.
+                let fields: Vec<String> = headers
+                    .iter()
+                    .zip(row)
+                    .map(|(header, value)| {
+                        format!(
+                            "{}:{}",
+                            Value::from(header.as_str()),
+                            Value::from(value.as_str())
+                        )
+                    })
+                    .collect();
+                let separator = if *first { "[\n  " } else { ",\n  " };
+                *first = false;
+
+                write!(writer, "{separator}{{{}}}", fields.join(",")).map_err(CliError::Io)
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), CliError> {
+        match self {
+            Sink::Delimited(mut writer) => writer.flush().map_err(CliError::Io),
+            Sink::Json { mut writer, first } => {
+                let closing = if first { "[]\n" } else { "\n]\n" };
+                writer
+                    .write_all(closing.as_bytes())
+                    .and_then(|_| writer.flush())
+                    .map_err(CliError::Io)
+            }
+        }
+    }
+}
+
+fn csv_to_io(err: csv::Error) -> CliError {
+    CliError::Io(err.into())
+}
+
+/// Finds the position of a column in the header
+fn column_index(headers: &csv::StringRecord, name: &str) -> Result<usize, CliError> {
+    headers
+        .iter()
+        .position(|header| header.trim() == name)
+        .ok_or_else(|| CliError::MissingColumn(name.to_string()))
+}
+
+/// Reads CSV rows from `reader`, converts each one with `convert`, and writes
+/// them with the converted columns appended to `writer`. Rows that fail are
+/// written with an `error`, and returned in the `Summary`, without stopping.
+pub fn convert_rows<R, W, F>(
+    reader: R,
+    writer: W,
+    format: OutputFormat,
+    columns: &Columns,
+    to: &iso::Currency,
+    default_date: Option<NaiveDate>,
+    mut convert: F,
+) -> Result<Summary, CliError>
+where
+    R: Read,
+    W: Write,
+    F: FnMut(Decimal, &iso::Currency, NaiveDate) -> Result<Converted, ConversionError>,
+{
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = reader.headers().map_err(csv_to_io)?.clone();
+    let amount_index = column_index(&headers, &columns.amount)?;
+    let currency_index = column_index(&headers, &columns.currency)?;
+    let date_index = columns
+        .date
+        .as_ref()
+        .map(|name| column_index(&headers, name))
+        .transpose()?;
+
+    let output_headers: Vec<String> = headers
+        .iter()
+        .map(String::from)
+        .chain(APPENDED_COLUMNS.iter().map(|column| column.to_string()))
+        .collect();
+
+    let mut sink = Sink::new(writer, format);
+    if let Sink::Delimited(writer) = &mut sink {
+        writer.write_record(&output_headers).map_err(csv_to_io)?;
+    }
+
+    let mut summary = Summary::default();
+
+    for record in reader.records() {
+        let record = record.map_err(csv_to_io)?;
+        let line = record.position().map(|pos| pos.line()).unwrap_or_default();
+        let field = |index: usize| record.get(index).unwrap_or_default().trim();
+
+        // Missing trailing fields are read as empty, but extra ones can't be
+        // told apart from shifted columns
+        let result = if record.len() > headers.len() {
+            Err(format!(
+                "found {} fields, but the header has {}",
+                record.len(),
+                headers.len()
+            ))
+        } else {
+            parse_row(
+                field(amount_index),
+                field(currency_index),
+                date_index.map(field),
+                default_date,
+            )
+            .and_then(|(amount, currency, date)| {
+                convert(amount, currency, date)
+                    .map(|converted| (converted, date))
+                    .map_err(|err| err.to_string())
+            })
+        };
+
+        let appended = match result {
+            Ok((converted, date)) => {
+                summary.converted += 1;
+
+                vec![
+                    converted.amount.to_string(),
+                    to.iso_alpha_code.to_string(),
+                    converted.rate.to_string(),
+                    date.to_string(),
+                    converted.provenance.as_str().to_string(),
+                    String::new(),
+                ]
+            }
+            Err(message) => {
+                let mut appended = vec![String::new(); APPENDED_COLUMNS.len() - 1];
+                appended.push(message.clone());
+                summary.failures.push(RowFailure { line, message });
+
+                appended
+            }
+        };
+
+        // Every row has as many input columns as the header, so the appended
+        // ones line up
+        let row: Vec<String> = record
+            .iter()
+            .map(String::from)
+            .chain(std::iter::repeat(String::new()))
+            .take(headers.len())
+            .chain(appended)
+            .collect();
+        sink.write(&output_headers, &row)?;
+    }
+
+    sink.finish()?;
+
+    Ok(summary)
+}
+
+/// Parses the amount, currency, and date of a row
+fn parse_row(
+    amount: &str,
+    currency: &str,
+    date: Option<&str>,
+    default_date: Option<NaiveDate>,
+) -> Result<(Decimal, &'static iso::Currency, NaiveDate), String> {
+    let amount: Decimal = amount
+        .parse()
+        .map_err(|_| format!("invalid amount {amount:?}"))?;
+    let currency = iso::find(currency.to_uppercase().as_str())
+        .ok_or_else(|| format!("invalid currency {currency:?}"))?;
+    let date = match date {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("invalid date {date:?}"))?,
+        None => default_date.ok_or("no date column, and the store has no latest date")?,
+    };
+
+    Ok((amount, currency, date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pretends every currency is worth two of the target currency, except
+    /// for dates in 2000 which have no rates.
+    fn fake_convert(
+        amount: Decimal,
+        _currency: &iso::Currency,
+        date: NaiveDate,
+    ) -> Result<Converted, ConversionError> {
+        if date < NaiveDate::from_ymd_opt(2001, 1, 1).unwrap() {
+            return Err(ConversionError::NoExchangeRate(date));
+        }
+
+        Ok(Converted {
+            amount: amount * Decimal::TWO,
+            rate: Decimal::TWO,
+            provenance: Provenance::Observed,
+        })
+    }
+
+    fn columns() -> Columns {
+        Columns {
+            amount: "Amount".to_string(),
+            currency: "Currency".to_string(),
+            date: Some("Date".to_string()),
+        }
+    }
+
+    #[test]
+    fn it_appends_converted_columns_and_keeps_going_on_failures() {
+        let input = "\
+Date,Description,Amount,Currency
+2023-05-04,Lunch,10.50,usd
+2000-01-01,Too old,5,USD
+2023-05-04,Bad currency,5,XXX
+2023-05-04,Bad amount,five,USD
+";
+        let mut output = Vec::new();
+        let summary = convert_rows(
+            input.as_bytes(),
+            &mut output,
+            OutputFormat::Csv,
+            &columns(),
+            iso::EUR,
+            None,
+            fake_convert,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Summary {
+                converted: 1,
+                failures: vec![
+                    RowFailure {
+                        line: 3,
+                        message: "could not find the relevant exchange rate on date 2000-01-01"
+                            .to_string(),
+                    },
+                    RowFailure {
+                        line: 4,
+                        message: "invalid currency \"XXX\"".to_string(),
+                    },
+                    RowFailure {
+                        line: 5,
+                        message: "invalid amount \"five\"".to_string(),
+                    },
+                ],
+            },
+            summary
+        );
+        insta::assert_snapshot!(String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn it_writes_json() {
+        let input = "Date,Amount,Currency\n2023-05-04,1,USD\n";
+        let mut output = Vec::new();
+
+        convert_rows(
+            input.as_bytes(),
+            &mut output,
+            OutputFormat::Json,
+            &columns(),
+            iso::EUR,
+            None,
+            fake_convert,
+        )
+        .unwrap();
+
+        let rows: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!("2", rows[0]["converted_amount"]);
+        assert_eq!("observed", rows[0]["provenance"]);
+    }
+
+    #[test]
+    fn it_keeps_the_order_and_repeats_of_columns_in_json() {
+        let input = "Note,Currency,Amount,Note,Date\nfirst,USD,1,second,2023-05-04\n";
+        let mut output = Vec::new();
+
+        convert_rows(
+            input.as_bytes(),
+            &mut output,
+            OutputFormat::Json,
+            &columns(),
+            iso::EUR,
+            None,
+            fake_convert,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            concat!(
+                r#"  {"Note":"first","Currency":"USD","Amount":"1","Note":"second","#,
+                r#""Date":"2023-05-04","converted_amount":"2","converted_currency":"EUR","#,
+                r#""rate":"2","rate_date":"2023-05-04","provenance":"observed","error":""}"#
+            ),
+            output.lines().nth(1).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_lines_up_ragged_rows() {
+        let input = "\
+Date,Amount,Currency,Note
+2023-05-04,1,USD
+2023-05-04,2,USD,Dinner,56.80
+";
+        let mut output = Vec::new();
+        let summary = convert_rows(
+            input.as_bytes(),
+            &mut output,
+            OutputFormat::Csv,
+            &columns(),
+            iso::EUR,
+            None,
+            fake_convert,
+        )
+        .unwrap();
+
+        assert_eq!(1, summary.converted);
+        assert_eq!(
+            vec![RowFailure {
+                line: 3,
+                message: "found 5 fields, but the header has 4".to_string(),
+            }],
+            summary.failures
+        );
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!("2023-05-04,1,USD,,2,EUR,2,2023-05-04,observed,", lines[1]);
+        assert_eq!(
+            "2023-05-04,2,USD,Dinner,,,,,,\"found 5 fields, but the header has 4\"",
+            lines[2]
+        );
+
+        let mut json = Vec::new();
+        convert_rows(
+            input.as_bytes(),
+            &mut json,
+            OutputFormat::Json,
+            &columns(),
+            iso::EUR,
+            None,
+            fake_convert,
+        )
+        .unwrap();
+
+        let rows: Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!("", rows[0]["Note"]);
+        assert_eq!("2", rows[0]["converted_amount"]);
+        assert_eq!("Dinner", rows[1]["Note"]);
+        assert_eq!("found 5 fields, but the header has 4", rows[1]["error"]);
+    }
+
+    #[test]
+    fn it_uses_the_default_date_without_a_date_column() {
+        let input = "Amount,Currency\n1,USD\n";
+        let mut output = Vec::new();
+        let columns = Columns {
+            date: None,
+            ..columns()
+        };
+
+        convert_rows(
+            input.as_bytes(),
+            &mut output,
+            OutputFormat::Tsv,
+            &columns,
+            iso::EUR,
+            NaiveDate::from_ymd_opt(2023, 5, 12),
+            fake_convert,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            "1\tUSD\t2\tEUR\t2\t2023-05-12\tobserved\t",
+            output.lines().nth(1).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_fails_on_missing_columns() {
+        let input = "Amount,Money\n1,USD\n";
+
+        assert!(matches!(
+            convert_rows(
+                input.as_bytes(),
+                Vec::new(),
+                OutputFormat::Csv,
+                &columns(),
+                iso::EUR,
+                None,
+                fake_convert,
+            ),
+            Err(CliError::MissingColumn(column)) if column == "Currency"
+        ));
+    }
+}
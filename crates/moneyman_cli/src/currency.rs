@@ -8,6 +8,13 @@ use rusty_money::iso;
 #[derive(Clone, Debug)]
 pub struct Currency(pub iso::Currency);
 
+impl Currency {
+    /// Finds a currency by its ISO alpha code, regardless of case
+    pub fn find(code: &str) -> Option<Currency> {
+        iso::find(code.to_uppercase().as_str()).map(|currency| Currency(*currency))
+    }
+}
+
 impl ValueParserFactory for Currency {
     type Parser = CurrencyParser;
 
@@ -31,8 +38,8 @@ impl TypedValueParser for CurrencyParser {
         let curr_str = value.to_str().map(|str| str.to_uppercase());
 
         match curr_str {
-            Some(str) => match iso::find(str.as_str()) {
-                Some(currency) => Ok(Currency(*currency)),
+            Some(str) => match Currency::find(str.as_str()) {
+                Some(currency) => Ok(currency),
                 None => {
                     let mut err = clap::Error::new(ErrorKind::InvalidValue).with_cmd(cmd);
 
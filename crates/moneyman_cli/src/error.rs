@@ -0,0 +1,262 @@
+use chrono::NaiveDate;
+use moneyman::{ConversionError, InitError, InvalidPeriod, SyncError};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::output::OutputFormat;
+
+/// Exit codes of the CLI. Usage errors exit with 2, same as any other clap
+/// program. Every `ConversionError`, and `SyncError` variant has its own
+/// code.
+pub const EXIT_CODES: &str = "\
+Exit codes:
+   0  Success
+   1  Unable to read or write a file, or any other error
+   2  Invalid arguments
+  10  The local data store is malformed
+  11  No exchange rate on the requested date
+  12  The currency is invalid, or not recorded by the ECB
+  13  Converting a currency to itself
+  14  The fees of a quote are more than the amount
+  15  No exchange rate at all in the requested period
+  16  The period doesn't exist, e.g month 13
+  20  The ECB history is missing from the data directory
+  21  Unable to open the local data store
+  22  Unable to seed the local data store
+  23  Unable to download the ECB history
+  24  Invalid sync options
+  25  The downloaded ECB history is invalid
+  26  Unable to delete the local data store
+  27  The local data store has no rates
+  28  Some rows of `convert-file` could not be converted
+  29  A column given to `convert-file` is missing from the input
+  30  A quick conversion query couldn't be understood
+  31  The config file, or a value given to `config set` is invalid
+  32  Missing rates couldn't be interpolated while syncing";
+
+/// Anything that makes the CLI exit with a non-zero code
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error(transparent)]
+    Conversion(#[from] ConversionError),
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+    #[error(transparent)]
+    Init(#[from] InitError),
+    /// `sync --force` couldn't delete the existing files
+    #[error("unable to delete local data store files")]
+    Delete,
+    /// The store doesn't have a single rate
+    #[error("unable to fetch the latest date from the local data store")]
+    NoLatestDate,
+    /// Reading, or writing a file failed
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    /// Some rows couldn't be converted, but the rest were
+    #[error("{failed} of {total} rows could not be converted")]
+    RowsFailed { failed: usize, total: usize },
+    /// The input doesn't have a column with this name
+    #[error("the input has no column named {0:?}")]
+    MissingColumn(String),
+    /// A query without a subcommand, e.g `100 usd jpy`, couldn't be parsed
+    #[error("{0}")]
+    Query(String),
+    /// The config file couldn't be parsed, or has invalid values
+    #[error("invalid config: {0}")]
+    Config(String),
+}
+
+impl From<InvalidPeriod> for CliError {
+    fn from(err: InvalidPeriod) -> Self {
+        CliError::Conversion(err.into())
+    }
+}
+
+/// The JSON printed to stderr on failure with `--output json`
+#[derive(Debug, Serialize)]
+struct ErrorReport<'a> {
+    error: ErrorBody<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody<'a> {
+    /// Stable, machine-readable name of the error
+    kind: &'a str,
+    /// Human readable description
+    message: String,
+    /// Same as the process' exit code
+    exit_code: i32,
+    /// Only present for `no_exchange_rate`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<NaiveDate>,
+}
+
+impl CliError {
+    /// What the process exits with. See `EXIT_CODES`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Conversion(ConversionError::MalformedExchangeStore) => 10,
+            CliError::Conversion(ConversionError::NoExchangeRate(_)) => 11,
+            CliError::Conversion(ConversionError::InvalidCurrency(_)) => 12,
+            CliError::Conversion(ConversionError::SameCurrency) => 13,
+            CliError::Conversion(ConversionError::FeesExceedAmount) => 14,
+            CliError::Conversion(ConversionError::NoExchangeRates(_, _)) => 15,
+            CliError::Conversion(ConversionError::InvalidPeriod(_)) => 16,
+            CliError::Conversion(_) => 1,
+            CliError::Sync(SyncError::NoEcbHistory) => 20,
+            CliError::Sync(SyncError::CouldNotRead) => 21,
+            CliError::Init(InitError::CouldNotRead) => 21,
+            CliError::Sync(SyncError::Seed) => 22,
+            CliError::Sync(SyncError::Interpolation(_)) => 32,
+            CliError::Sync(SyncError::Download) => 23,
+            CliError::Sync(SyncError::InvalidOptions(_)) => 24,
+            CliError::Sync(SyncError::InvalidHistory(_)) => 25,
+            CliError::Sync(_) => 1,
+            CliError::Delete => 26,
+            CliError::NoLatestDate => 27,
+            CliError::Io(_) => 1,
+            CliError::RowsFailed { .. } => 28,
+            CliError::MissingColumn(_) => 29,
+            CliError::Query(_) => 30,
+            CliError::Config(_) => 31,
+        }
+    }
+
+    /// Stable, machine-readable name of the error
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CliError::Conversion(ConversionError::MalformedExchangeStore) => "malformed_store",
+            CliError::Conversion(ConversionError::NoExchangeRate(_)) => "no_exchange_rate",
+            CliError::Conversion(ConversionError::InvalidCurrency(_)) => "invalid_currency",
+            CliError::Conversion(ConversionError::SameCurrency) => "same_currency",
+            CliError::Conversion(ConversionError::FeesExceedAmount) => "fees_exceed_amount",
+            CliError::Conversion(ConversionError::NoExchangeRates(_, _)) => "no_exchange_rates",
+            CliError::Conversion(ConversionError::InvalidPeriod(_)) => "invalid_period",
+            CliError::Conversion(_) => "conversion",
+            CliError::Sync(SyncError::NoEcbHistory) => "no_ecb_history",
+            CliError::Sync(SyncError::CouldNotRead) | CliError::Init(InitError::CouldNotRead) => {
+                "could_not_read"
+            }
+            CliError::Sync(SyncError::Seed) => "seed",
+            CliError::Sync(SyncError::Interpolation(_)) => "interpolation",
+            CliError::Sync(SyncError::Download) => "download",
+            CliError::Sync(SyncError::InvalidOptions(_)) => "invalid_sync_options",
+            CliError::Sync(SyncError::InvalidHistory(_)) => "invalid_history",
+            CliError::Sync(_) => "sync",
+            CliError::Delete => "delete",
+            CliError::NoLatestDate => "no_latest_date",
+            CliError::Io(_) => "io",
+            CliError::RowsFailed { .. } => "rows_failed",
+            CliError::MissingColumn(_) => "missing_column",
+            CliError::Query(_) => "invalid_query",
+            CliError::Config(_) => "invalid_config",
+        }
+    }
+
+    /// A friendlier explanation than `Display`, with hints on what to do next
+    fn hint(&self) -> String {
+        match self {
+            CliError::Conversion(ConversionError::MalformedExchangeStore) => {
+                "The local data store may have been corrupted. You could try syncing it with `--force`.".to_string()
+            }
+            CliError::Conversion(ConversionError::NoExchangeRate(date)) => format!(
+                "No available rates on date {}. Some options:\n\n\t1. Sync with the latest ECB rates if you haven't already; or\n\t2. Use the --fallback flag to attempt to interpolate the rates",
+                date
+            ),
+            CliError::Conversion(ConversionError::InvalidCurrency(currency)) => format!(
+                "The currency {currency} may either be invalid, or is currently not recorded by the European Central Bank."
+            ),
+            CliError::Conversion(ConversionError::SameCurrency) => {
+                "It's 1. ONEEEEEEEEEEEEEEEEEEEEE".to_string()
+            }
+            CliError::Conversion(ConversionError::FeesExceedAmount) => {
+                "The fees would take all of the amount, and then some. Check the --fee values, e.g 0.5% instead of 0.5.".to_string()
+            }
+            CliError::Conversion(ConversionError::NoExchangeRates(start, end)) => format!(
+                "No available rates from {start} to {end}. The local data store may not cover the period yet, so try syncing it with the latest ECB rates."
+            ),
+            CliError::NoLatestDate => {
+                "Unable to fetch the latest date from the local data store. Have you tried syncing it with ECB?".to_string()
+            }
+            err => err.to_string(),
+        }
+    }
+
+    fn date(&self) -> Option<NaiveDate> {
+        match self {
+            CliError::Conversion(ConversionError::NoExchangeRate(date)) => Some(*date),
+            _ => None,
+        }
+    }
+
+    /// Renders the error for stderr in the given format
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => serde_json::to_string_pretty(&ErrorReport {
+                error: ErrorBody {
+                    kind: self.kind(),
+                    message: self.to_string(),
+                    exit_code: self.exit_code(),
+                    date: self.date(),
+                },
+            })
+            .expect("errors are always serializable"),
+            OutputFormat::Plain | OutputFormat::Human | OutputFormat::Csv | OutputFormat::Tsv => {
+                self.hint()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_renders_errors_as_json() {
+        let err = CliError::from(ConversionError::NoExchangeRate(
+            NaiveDate::from_ymd_opt(2023, 5, 6).unwrap(),
+        ));
+
+        insta::assert_snapshot!(err.render(OutputFormat::Json));
+    }
+
+    #[test]
+    fn it_gives_every_error_a_distinct_exit_code() {
+        let errors = [
+            CliError::Conversion(ConversionError::MalformedExchangeStore),
+            CliError::Conversion(ConversionError::NoExchangeRate(NaiveDate::MIN)),
+            CliError::Conversion(ConversionError::InvalidCurrency(*rusty_money::iso::PHP)),
+            CliError::Conversion(ConversionError::SameCurrency),
+            CliError::Conversion(ConversionError::FeesExceedAmount),
+            CliError::Conversion(ConversionError::NoExchangeRates(
+                NaiveDate::MIN,
+                NaiveDate::MAX,
+            )),
+            CliError::Conversion(ConversionError::InvalidPeriod(InvalidPeriod(String::new()))),
+            CliError::Sync(SyncError::NoEcbHistory),
+            CliError::Sync(SyncError::CouldNotRead),
+            CliError::Sync(SyncError::Seed),
+            CliError::Sync(SyncError::Interpolation(String::new())),
+            CliError::Sync(SyncError::Download),
+            CliError::Sync(SyncError::InvalidOptions(String::new())),
+            CliError::Sync(SyncError::InvalidHistory(String::new())),
+            CliError::Delete,
+            CliError::NoLatestDate,
+            CliError::Io(std::io::ErrorKind::NotFound.into()),
+            CliError::RowsFailed {
+                failed: 1,
+                total: 2,
+            },
+            CliError::MissingColumn(String::new()),
+            CliError::Query(String::new()),
+            CliError::Config(String::new()),
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(CliError::exit_code).collect();
+        codes.sort();
+        codes.dedup();
+
+        assert_eq!(errors.len(), codes.len());
+        assert!(codes.iter().all(|code| *code != 0 && *code != 2));
+    }
+}
@@ -0,0 +1,166 @@
+use rust_decimal::Decimal;
+use rusty_money::{iso, Formatter, LocalFormat, Money, Params, Position};
+
+/// How a locale writes numbers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Locale {
+    /// BCP 47 tag, e.g `de-DE`
+    pub tag: &'static str,
+    /// Separates groups of digits, e.g the `.` in `1.234,56`
+    pub digit_separator: char,
+    /// Separates the minor units, e.g the `,` in `1.234,56`
+    pub decimal_separator: char,
+    /// Sizes of the digit groups from the right, e.g `[3, 2, 2, ..]` for
+    /// `1,23,45,678`
+    pub grouping: [usize; 6],
+}
+
+const THOUSANDS: [usize; 6] = [3; 6];
+const LAKHS: [usize; 6] = [3, 2, 2, 2, 2, 2];
+
+const fn locale(tag: &'static str, digit_separator: char, decimal_separator: char) -> Locale {
+    Locale {
+        tag,
+        digit_separator,
+        decimal_separator,
+        grouping: THOUSANDS,
+    }
+}
+
+/// Every locale `--locale` knows about. The `en-EU`, and `en-BY` ones only
+/// exist since they're what `rusty_money` uses for some currencies.
+pub const LOCALES: [Locale; 17] = [
+    locale("en-US", ',', '.'),
+    locale("en-GB", ',', '.'),
+    Locale {
+        grouping: LAKHS,
+        ..locale("en-IN", ',', '.')
+    },
+    locale("en-EU", '.', ','),
+    locale("en-BY", ' ', ','),
+    locale("de-DE", '.', ','),
+    locale("de-AT", ' ', ','),
+    locale("de-CH", '\'', '.'),
+    locale("fr-FR", ' ', ','),
+    locale("es-ES", '.', ','),
+    locale("it-IT", '.', ','),
+    locale("nl-NL", '.', ','),
+    locale("pt-BR", '.', ','),
+    locale("pl-PL", ' ', ','),
+    locale("ru-RU", ' ', ','),
+    locale("ja-JP", ',', '.'),
+    locale("zh-CN", ',', '.'),
+];
+
+impl Locale {
+    /// Finds a locale by its tag, ignoring case. POSIX names like
+    /// `de_DE.UTF-8` work too.
+    pub fn find(tag: &str) -> Option<Locale> {
+        let tag = tag.split(['.', '@']).next()?.replace('_', "-");
+
+        LOCALES
+            .into_iter()
+            .find(|locale| locale.tag.eq_ignore_ascii_case(&tag))
+    }
+
+    /// The locale `rusty_money` writes `currency` in, e.g `en-EU` for EUR
+    pub fn of(currency: &iso::Currency) -> Locale {
+        let format = LocalFormat::from_locale(currency.locale);
+        let tag = match format.name {
+            "en-in" => "en-IN",
+            "en-eu" => "en-EU",
+            "en-by" => "en-BY",
+            _ => "en-US",
+        };
+
+        Locale::find(tag).expect("every rusty_money locale is listed")
+    }
+}
+
+/// Parses `--locale` for clap
+pub fn parse_locale_arg(tag: &str) -> Result<Locale, String> {
+    Locale::find(tag).ok_or_else(|| {
+        let tags: Vec<&str> = LOCALES.iter().map(|locale| locale.tag).collect();
+
+        format!("unknown locale {tag:?}. Known locales: {}", tags.join(", "))
+    })
+}
+
+/// Writes `amount` of `currency` with its symbol, e.g `€1.234,56`, or
+/// `¥1,235`. Digits are grouped the way `locale` does, or the way the
+/// currency usually is if there's no locale. The symbol goes where the
+/// currency puts it. Amounts with fewer decimal places than the minor units
+/// of the currency are padded, but never rounded.
+pub fn format_money(amount: Decimal, currency: &iso::Currency, locale: Option<&Locale>) -> String {
+    let locale = locale.copied().unwrap_or_else(|| Locale::of(currency));
+    let mut amount = amount;
+    if amount.scale() < currency.exponent {
+        amount.rescale(currency.exponent);
+    }
+
+    let positions = match currency.symbol_first {
+        true => vec![Position::Sign, Position::Symbol, Position::Amount],
+        false => vec![
+            Position::Sign,
+            Position::Amount,
+            Position::Space,
+            Position::Symbol,
+        ],
+    };
+
+    Formatter::money(
+        &Money::from_decimal(amount, currency),
+        Params {
+            digit_separator: locale.digit_separator,
+            exponent_separator: locale.decimal_separator,
+            separator_pattern: locale.grouping.to_vec(),
+            positions,
+            rounding: None,
+            symbol: Some(currency.symbol),
+            code: Some(currency.iso_alpha_code),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(amount: &str, currency: &iso::Currency, tag: Option<&str>) -> String {
+        format_money(
+            amount.parse().unwrap(),
+            currency,
+            tag.and_then(Locale::find).as_ref(),
+        )
+    }
+
+    #[test]
+    fn it_formats_money_in_a_locale() {
+        assert_eq!("€1.234,56", format("1234.56", iso::EUR, Some("de-DE")));
+        assert_eq!("€1,234.56", format("1234.56", iso::EUR, Some("en-US")));
+        assert_eq!(
+            "$1'234'567.50",
+            format("1234567.5", iso::USD, Some("de_CH.UTF-8"))
+        );
+        assert_eq!("₹12,34,567.00", format("1234567", iso::INR, Some("en-IN")));
+        assert_eq!("-$0.25", format("-0.25", iso::USD, Some("en-US")));
+    }
+
+    #[test]
+    fn it_uses_the_currency_conventions_without_a_locale() {
+        assert_eq!("¥1,235", format("1235", iso::JPY, None));
+        assert_eq!("€1.234,56", format("1234.56", iso::EUR, None));
+        assert_eq!("1.234,56 kr.", format("1234.56", iso::DKK, Some("de-DE")));
+        // Already rounded amounts keep their precision
+        assert_eq!("$3.1416", format("3.1416", iso::USD, None));
+    }
+
+    #[test]
+    fn it_rejects_unknown_locales() {
+        assert!(parse_locale_arg("xx-XX").is_err());
+        assert_eq!(
+            Ok("fr-FR"),
+            parse_locale_arg("FR-fr").map(|locale| locale.tag)
+        );
+    }
+}
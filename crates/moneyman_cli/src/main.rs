@@ -1,24 +1,98 @@
+mod chart;
+mod completions;
+mod config;
+mod convert_file;
 mod currency;
+mod error;
+mod locale;
+mod output;
+mod pricing;
+mod query;
+mod report;
+mod shell;
 
-use std::path::PathBuf;
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::PathBuf,
+    process::ExitCode,
+    time::Duration,
+};
 
 use chrono::NaiveDate;
+use config::Config;
 use currency::Currency;
-use moneyman::{ConversionError, ExchangeStore};
+use error::CliError;
+use locale::Locale;
+use moneyman::{
+    AverageMethod, ConversionError, ConversionOptions, DatedRate, ExchangeStore, Fee,
+    InvalidPeriod, Ohlc, Period, RateStatus, Resample, Rounding, RoundingMode, SyncOptions,
+    SyncOutcome,
+};
+use output::OutputFormat;
 use rust_decimal::Decimal;
 
-use clap::{Command, Parser, Subcommand};
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
 use rusty_money::{iso, Money};
 
 #[derive(Debug, Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    name = "moneyman",
+    author,
+    version,
+    about,
+    long_about = None,
+    after_help = error::EXIT_CODES
+)]
 struct Cli {
     // /// Turn debugging information on
     // #[arg(short, long)]
     // debug: bool,
+    /// How results are printed. Errors always go to stderr. Default: plain
+    #[arg(short, long, global = true, value_enum, visible_alias = "format")]
+    output: Option<OutputFormat>,
+    /// How `--output human` writes amounts, e.g de-DE. Default: the
+    /// conventions of each currency
+    #[arg(long, global = true, value_parser = locale::parse_locale_arg)]
+    locale: Option<Locale>,
+    /// Never sync automatically, whatever the config says. Fails if the local
+    /// data store has never been synced. `moneyman sync` still syncs.
+    #[arg(long, global = true)]
+    offline: bool,
+    /// How converted amounts are rounded. Default: exact, or half-even with
+    /// --precision, or --output human
+    #[arg(long, global = true, value_name = "MODE", value_parser = query::rounding_mode_parser())]
+    round: Option<RoundingMode>,
+    /// Decimal places to round converted amounts to. Default: the minor units
+    /// of the target currency, e.g 2 for USD, or 0 for JPY
+    #[arg(long, global = true, value_name = "DIGITS")]
+    precision: Option<u32>,
+
+    /// A quick conversion without a subcommand, e.g `100 usd jpy`, `100usd in
+    /// eur yesterday`, or `5 gbp to usd,jpy on last friday`
+    #[arg(value_name = "QUERY", allow_hyphen_values = true)]
+    query: Vec<String>,
+    /// Interpolate missing rates for a QUERY based on the neighboring dates
+    /// with rates.
+    #[arg(long)]
+    fallback: bool,
+    /// Don't interpolate missing rates for a QUERY, even if the config says
+    /// so.
+    #[arg(long, conflicts_with = "fallback")]
+    no_fallback: bool,
+
     /// What can moneyman do?
     #[command(subcommand)]
-    commands: Option<Commands>,
+    commands: Option<AnyCommand>,
+}
+
+/// Every subcommand, split by whether it needs the config
+#[derive(Debug, Subcommand)]
+enum AnyCommand {
+    #[command(flatten)]
+    Store(Commands),
+    #[command(flatten)]
+    Setup(SetupCommands),
 }
 
 #[derive(Debug, Subcommand)]
@@ -28,6 +102,32 @@ enum Commands {
         #[arg(short, long)]
         /// Don't do this unless you known the exchange store is messed up
         force: bool,
+
+        /// Download the history from a mirror instead of the ECB. The archive
+        /// is expected at <URL>/eurofxref-hist.zip
+        #[arg(long, value_name = "URL")]
+        base_url: Option<String>,
+        /// Send all requests through this proxy. e.g http://proxy.corp:3128
+        #[arg(long, value_name = "URL")]
+        proxy: Option<String>,
+        /// Give up on an attempt after this many seconds. Default: 60
+        #[arg(long, value_name = "SECONDS")]
+        timeout: Option<u64>,
+        /// How many times to retry a failed download. Default: 2
+        #[arg(long, value_name = "COUNT")]
+        retries: Option<u32>,
+        /// Override the User-Agent header
+        #[arg(long, value_name = "USER_AGENT")]
+        user_agent: Option<String>,
+        /// Extra header to send. Can be repeated. e.g "X-Api-Key: abc"
+        #[arg(long, value_name = "HEADER", value_parser = parse_header)]
+        header: Vec<(String, String)>,
+        /// PEM file with extra root certificates to trust
+        #[arg(long, value_name = "FILE_PATH")]
+        ca_bundle: Option<PathBuf>,
+        /// List every rate ECB corrected since the last sync
+        #[arg(long)]
+        show_revisions: bool,
         // TODO: Implement
         // /// Where moneyman will save its local data store. Default: ~/.moneyman
         // #[arg(long, value_name = "DIRECTORY_PATH")]
@@ -42,12 +142,14 @@ enum Commands {
         /// Source currency through ISO alpha code. e.g EUR
         #[arg(short, long, value_name = "CURRENCY")]
         from: Currency,
-        /// Target currency through ISO alpha code. e.g USD
-        #[arg(short, long, value_name = "CURRENCY")]
-        to: Currency,
+        /// Target currencies through ISO alpha codes. e.g USD, or
+        /// USD,GBP,JPY. Required unless `to` is set in the config.
+        #[arg(short, long, value_name = "CURRENCY", value_delimiter = ',')]
+        to: Vec<Currency>,
         /// Specify a specific date to convert. Will use the latest date in
-        /// the exchange store if not specified. e.g 2023-05-05
-        #[arg(long, value_name = "DATE")]
+        /// the exchange store if not specified. e.g 2023-05-05, yesterday,
+        /// last friday, -7d, or 2023-Q1-end
+        #[arg(long, value_name = "DATE", value_parser = query::parse_date_arg, allow_hyphen_values = true)]
         on: Option<NaiveDate>,
 
         // TODO: Implement
@@ -58,9 +160,361 @@ enum Commands {
         /// based on the neighboring dates with rates.
         #[arg(long)]
         fallback: bool,
+        /// Don't interpolate missing rates, even if the config says so
+        #[arg(long, conflicts_with = "fallback")]
+        no_fallback: bool,
+    },
+    /// Quote a conversion the way a bank, or exchange office would: at the
+    /// mid rate less a spread, after fees. The mid rate result is shown
+    /// alongside.
+    Quote {
+        /// The amount of money the customer hands over
+        #[arg(value_name = "AMOUNT")]
+        amount: Decimal,
+
+        /// Source currency through ISO alpha code. e.g EUR
+        #[arg(short, long, value_name = "CURRENCY")]
+        from: Currency,
+        /// Target currency through ISO alpha code. e.g USD. Required unless
+        /// `to` is set in the config.
+        #[arg(short, long, value_name = "CURRENCY")]
+        to: Option<Currency>,
+        /// Will use the latest date in the exchange store if not specified.
+        /// e.g 2023-05-05, or yesterday
+        #[arg(long, value_name = "DATE", value_parser = query::parse_date_arg, allow_hyphen_values = true)]
+        on: Option<NaiveDate>,
+
+        /// Spread below the mid rate, in percent or basis points. e.g 1.5%,
+        /// or 150bp. Prefix it with a pair to limit it to that direction,
+        /// e.g USD/JPY=25bp. Can be repeated.
+        #[arg(long, value_name = "SPREAD", value_parser = pricing::parse_spread_arg)]
+        spread: Vec<pricing::SpreadArg>,
+        /// Fee taken from the amount before it's converted, either fixed in
+        /// the source currency, or a percentage. e.g 2.50, or 0.5%. Can be
+        /// repeated.
+        #[arg(long, value_name = "FEE", value_parser = pricing::parse_fee_arg)]
+        fee: Vec<Fee>,
+
+        /// Interpolate missing rates based on the neighboring dates with
+        /// rates.
+        #[arg(long)]
+        fallback: bool,
+        /// Don't interpolate missing rates, even if the config says so
+        #[arg(long, conflicts_with = "fallback")]
+        no_fallback: bool,
+    },
+    /// Average the rate of a currency pair over a month, quarter, year, or
+    /// any range of dates, e.g for translating a P&L
+    Average {
+        /// e.g 2023-05, 2023-Q2, 2023, or 2023-04-06..2024-04-05. Either end
+        /// of a range can be a relative date, e.g 2023-01-01..yesterday
+        #[arg(value_name = "PERIOD", value_parser = query::parse_period_arg, allow_hyphen_values = true)]
+        period: Period,
+
+        /// Source currency through ISO alpha code. e.g USD
+        #[arg(short, long, value_name = "CURRENCY")]
+        from: Currency,
+        /// Target currency through ISO alpha code. e.g EUR. Required unless
+        /// `to` is set in the config.
+        #[arg(short, long, value_name = "CURRENCY")]
+        to: Option<Currency>,
+        /// Average each month, or quarter of the period on its own
+        #[arg(long, value_enum, value_name = "INTERVAL")]
+        by: Option<Interval>,
+        /// Average every calendar day, with weekends, and holidays
+        /// interpolated, instead of only the days ECB published rates on
+        #[arg(long)]
+        calendar_days: bool,
+    },
+    /// The closing rate of a currency pair at the end of a month, quarter,
+    /// or year, e.g for revaluing a balance sheet. It's the rate of the last
+    /// date ECB published rates on in the period.
+    Closing {
+        /// e.g 2023-05, 2023-Q2, 2023, or 2023-04-06..2024-04-05
+        #[arg(value_name = "PERIOD", value_parser = query::parse_period_arg, allow_hyphen_values = true)]
+        period: Period,
+
+        /// Source currency through ISO alpha code. e.g USD
+        #[arg(short, long, value_name = "CURRENCY")]
+        from: Currency,
+        /// Target currency through ISO alpha code. e.g EUR. Required unless
+        /// `to` is set in the config.
+        #[arg(short, long, value_name = "CURRENCY")]
+        to: Option<Currency>,
+        /// List the closing rate of each month, or quarter of the period,
+        /// e.g `closing 2023 --by month` for every month-end of 2023
+        #[arg(long, value_enum, value_name = "INTERVAL")]
+        by: Option<Interval>,
+    },
+    /// Describe a currency pair over a period: its first, last, lowest, and
+    /// highest rates, mean, standard deviation, annualized volatility, and
+    /// change. Only the rates ECB published count.
+    Stats {
+        /// e.g 2023-05, 2023-Q2, 2023, or 2023-01-01..yesterday
+        #[arg(value_name = "PERIOD", value_parser = query::parse_period_arg, allow_hyphen_values = true)]
+        period: Period,
+
+        /// Source currency through ISO alpha code. e.g USD
+        #[arg(short, long, value_name = "CURRENCY")]
+        from: Currency,
+        /// Target currency through ISO alpha code. e.g EUR. Required unless
+        /// `to` is set in the config.
+        #[arg(short, long, value_name = "CURRENCY")]
+        to: Option<Currency>,
+    },
+    /// List the published rates of a currency pair over a period, or
+    /// resample them into weekly, or monthly open, high, low, and close
+    /// rates, with moving averages of the closing rates
+    History {
+        /// e.g 2023-05, 2023-Q2, 2023, or 2023-01-01..yesterday
+        #[arg(value_name = "PERIOD", value_parser = query::parse_period_arg, allow_hyphen_values = true)]
+        period: Period,
+
+        /// Source currency through ISO alpha code. e.g USD
+        #[arg(short, long, value_name = "CURRENCY")]
+        from: Currency,
+        /// Target currency through ISO alpha code. e.g EUR. Required unless
+        /// `to` is set in the config.
+        #[arg(short, long, value_name = "CURRENCY")]
+        to: Option<Currency>,
+        #[arg(long, value_enum, value_name = "INTERVAL", default_value_t = Sampling::Daily)]
+        resample: Sampling,
+        /// Simple moving average of the last N points, e.g days, or months
+        /// with `--resample monthly`. Points before the first N don't have
+        /// one.
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        sma: Option<u16>,
+        /// Exponential moving average over N points, starting from the
+        /// simple one
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        ema: Option<u16>,
+    },
+    /// Draw a line chart of the rates of one or more currency pairs in the
+    /// terminal, or to an SVG, or PNG file with `--out`. Interpolated rates
+    /// are dotted. The other output formats list the rates instead.
+    Chart {
+        /// Source currency through ISO alpha code. e.g USD
+        #[arg(short, long, value_name = "CURRENCY")]
+        from: Currency,
+        /// Target currencies through ISO alpha codes. e.g JPY, or GBP,JPY.
+        /// Required unless `to` is set in the config.
+        #[arg(short, long, value_name = "CURRENCY", value_delimiter = ',')]
+        to: Vec<Currency>,
+        /// First date of the chart. e.g 2020-01-01, or -1y
+        #[arg(long, value_name = "DATE", value_parser = query::parse_date_arg, allow_hyphen_values = true)]
+        start: NaiveDate,
+        /// Last date of the chart. Today if not specified.
+        #[arg(long, value_name = "DATE", value_parser = query::parse_date_arg, allow_hyphen_values = true)]
+        end: Option<NaiveDate>,
+        /// Width of the chart in characters
+        #[arg(long, value_name = "COLUMNS", default_value_t = 60, value_parser = clap::value_parser!(u16).range(2..))]
+        width: u16,
+        /// Height of the chart in lines
+        #[arg(long, value_name = "LINES", default_value_t = 12, value_parser = clap::value_parser!(u16).range(2..))]
+        height: u16,
+        /// Rescale every pair to start at 100, to compare pairs of very
+        /// different magnitudes
+        #[arg(long)]
+        rebase: bool,
+        /// Write the chart to an SVG, or PNG file instead, with axes, a
+        /// legend, and gaps, and long interpolated stretches shaded. e.g
+        /// rates.svg
+        #[arg(long, value_name = "FILE_PATH", value_parser = chart::parse_out_arg)]
+        out: Option<PathBuf>,
+    },
+    /// Show what's in the local data store, and how fresh it is
+    Info,
+    /// List the rates of every currency on a date
+    Rates {
+        /// Will use the latest date in the exchange store if not specified.
+        /// e.g 2023-05-05, yesterday, last friday, -7d, or 2023-Q1-end
+        #[arg(long, value_name = "DATE", value_parser = query::parse_date_arg, allow_hyphen_values = true)]
+        on: Option<NaiveDate>,
+        /// Quote every rate against this currency instead of EUR. e.g USD
+        #[arg(short, long, value_name = "CURRENCY")]
+        base: Option<Currency>,
+    },
+    /// Show the rate of every one of several currencies in every other on a
+    /// date, e.g for a treasury's cross-rate table
+    Matrix {
+        /// Will use the latest date in the exchange store if not specified.
+        /// e.g 2023-05-05, or yesterday
+        #[arg(long, value_name = "DATE", value_parser = query::parse_date_arg, allow_hyphen_values = true)]
+        on: Option<NaiveDate>,
+        /// ISO alpha codes of the currencies, in the order they're shown. e.g
+        /// USD,GBP,JPY,CHF
+        #[arg(long, value_name = "CURRENCY", value_delimiter = ',', required = true)]
+        currencies: Vec<Currency>,
+    },
+    /// Start an interactive shell for quick lookups, e.g `100 usd to jpy`.
+    /// Type `help` in the shell to see what it can do.
+    Shell,
+    /// Convert every row of a CSV file, e.g a spreadsheet of expenses. The
+    /// converted amount, rate, and where the rate came from are appended to
+    /// each row.
+    ConvertFile {
+        /// CSV file to read. Reads from stdin if it's missing, or `-`
+        #[arg(value_name = "FILE_PATH")]
+        input: Option<PathBuf>,
+        /// Where to write the converted rows. Writes to stdout if missing
+        #[arg(long, value_name = "FILE_PATH")]
+        out: Option<PathBuf>,
+
+        /// Name of the column with the amounts
+        #[arg(long, value_name = "COLUMN")]
+        amount_col: String,
+        /// Name of the column with the ISO alpha codes of the amounts
+        #[arg(long, value_name = "COLUMN")]
+        currency_col: String,
+        /// Name of the column with the dates of the amounts. e.g 2023-05-05.
+        /// Uses the latest date in the exchange store if not specified.
+        #[arg(long, value_name = "COLUMN")]
+        date_col: Option<String>,
+        /// Target currency through ISO alpha code. e.g EUR. Required unless
+        /// `to` is set in the config.
+        #[arg(short, long, value_name = "CURRENCY")]
+        to: Option<Currency>,
+
+        /// Interpolate missing rates based on the neighboring dates with
+        /// rates.
+        #[arg(long)]
+        fallback: bool,
+        /// Don't interpolate missing rates, even if the config says so
+        #[arg(long, conflicts_with = "fallback")]
+        no_fallback: bool,
     },
 }
 
+/// Subcommands that don't read the config, so they keep working when it's
+/// broken
+#[derive(Debug, Subcommand)]
+enum SetupCommands {
+    /// Print a completion script for your shell, e.g `moneyman completions
+    /// bash > ~/.local/share/bash-completion/completions/moneyman`.
+    /// Currencies are completed from the local data store as it is whenever
+    /// you press tab.
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Print the man page, or write a page per subcommand to a directory
+    Man {
+        #[arg(long, value_name = "DIRECTORY_PATH")]
+        out_dir: Option<PathBuf>,
+    },
+    /// Read, or change the defaults in the config file. Flags always take
+    /// precedence over it.
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+/// How a period is split, e.g for `average --by month`
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Interval {
+    Month,
+    Quarter,
+}
+
+impl Interval {
+    fn split(&self, period: &Period) -> Result<Vec<Period>, InvalidPeriod> {
+        match self {
+            Interval::Month => period.months(),
+            Interval::Quarter => period.quarters(),
+        }
+    }
+}
+
+/// How `history` samples rates
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Sampling {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Sampling {
+    fn name(&self) -> &'static str {
+        match self {
+            Sampling::Daily => "daily",
+            Sampling::Weekly => "weekly",
+            Sampling::Monthly => "monthly",
+        }
+    }
+}
+
+/// Lists the published rates of `from` in `to` over a period, resampled, and
+/// with moving averages if asked to
+fn history(
+    store: &ExchangeStore,
+    from: &iso::Currency,
+    to: &iso::Currency,
+    period: Period,
+    resample: Sampling,
+    sma: Option<usize>,
+    ema: Option<usize>,
+) -> Result<report::History, CliError> {
+    let (start, end) = period.bounds()?;
+    let published: Vec<DatedRate> = store
+        .rates_between(from, to, start, end)?
+        .into_iter()
+        .filter(|rate| rate.status == RateStatus::Observed)
+        .collect();
+    if published.is_empty() {
+        return Err(ConversionError::NoExchangeRates(start, end).into());
+    }
+
+    let ohlc: Vec<Ohlc> = match resample {
+        Sampling::Daily => published
+            .iter()
+            .map(|rate| Ohlc {
+                start: rate.date,
+                end: rate.date,
+                open: rate.rate,
+                high: rate.rate,
+                low: rate.rate,
+                close: rate.rate,
+                observations: 1,
+            })
+            .collect(),
+        Sampling::Weekly => moneyman::resample(&published, Resample::Weekly),
+        Sampling::Monthly => moneyman::resample(&published, Resample::Monthly),
+    };
+    let closes: Vec<Decimal> = ohlc.iter().map(|ohlc| ohlc.close).collect();
+    let smas = sma.map(|window| moneyman::simple_moving_average(&closes, window));
+    let emas = ema.map(|window| moneyman::exponential_moving_average(&closes, window));
+
+    let points = ohlc
+        .iter()
+        .enumerate()
+        .map(|(index, ohlc)| report::HistoryPoint {
+            sma: smas.as_ref().and_then(|smas| smas[index]),
+            ema: emas.as_ref().and_then(|emas| emas[index]),
+            ..report::HistoryPoint::from(ohlc)
+        })
+        .collect();
+
+    Ok(report::History {
+        from: from.iso_alpha_code,
+        to: to.iso_alpha_code,
+        resample: resample.name(),
+        sma,
+        ema,
+        points,
+    })
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigAction {
+    /// Print the value of a key, e.g `http.proxy`. Prints nothing if unset.
+    Get { key: String },
+    /// Set a key, e.g `moneyman config set to EUR`
+    Set { key: String, value: String },
+    /// List every key, and its value
+    List,
+}
+
 const MONEYMAN: &str = "
  /$$      /$$  /$$$$$$  /$$   /$$ /$$$$$$$$ /$$     /$$ /$$      /$$  /$$$$$$  /$$   /$$
 | $$$    /$$$ /$$__  $$| $$$ | $$| $$_____/|  $$   /$$/| $$$    /$$$ /$$__  $$| $$$ | $$
@@ -72,171 +526,758 @@ const MONEYMAN: &str = "
 |__/     |__/ \\______/ |__/  \\__/|________/    |__/    |__/     |__/|__/  |__/|__/  \\__/
 ";
 
-fn print_result_no_fallback(
-    from_amount: Money<iso::Currency>,
-    converted_amount: Result<Money<iso::Currency>, ConversionError>,
-    date: NaiveDate,
-) {
-    match converted_amount {
-        Ok(money) => {
-            println!(
-                "{} {} -> {} {} on {}",
-                from_amount.amount(),
-                from_amount.currency(),
-                money.amount(),
-                money.currency(),
-                date
-            );
-        }
-        Err(ConversionError::MalformedExchangeStore) => {
-            println!("The local data store may have been corrupted. You could try syncing it with `--force`.");
-        }
-        Err(ConversionError::NoExchangeRate(date)) => {
-            println!(
-                "No available rates on date {}. Some options:\n\n\t1. Sync with the latest ECB rates if you haven't already; or\n\t2. Use the --fallback flag to attempt to interpolate the rates",
-                date
-            );
+/// The headers of the config, with the ones given by `--header` on top.
+/// Names are case-insensitive.
+fn merge_headers(
+    config: Vec<(String, String)>,
+    flags: Vec<(String, String)>,
+) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = config
+        .into_iter()
+        .filter(|(name, _)| {
+            !flags
+                .iter()
+                .any(|(flag, _)| flag.eq_ignore_ascii_case(name))
+        })
+        .collect();
+    headers.extend(flags);
+
+    headers
+}
+
+/// Parses a `Name: value` header
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    header
+        .split_once(':')
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .ok_or_else(|| format!("expected `Name: value`, got `{header}`"))
+}
+
+/// Opens the store, syncing it first if it has never been synced, or if
+/// it's stale and the config says so. `offline` never syncs.
+fn init_or_get_store(config: &Config, offline: bool) -> Result<ExchangeStore, CliError> {
+    let data_dir = config.data_dir();
+    let policy = config.sync_policy(offline);
+    let initial = !data_dir.join("eurofxref-hist.db3").exists();
+
+    if initial && !policy.offline {
+        eprintln!("Running initial sync with ECB...");
+    }
+
+    let (store, outcome) =
+        moneyman::ExchangeStore::open_with_policy(data_dir, &config.sync_options(), &policy)?;
+
+    match outcome {
+        SyncOutcome::Synced(report) if initial => eprintln!(
+            "Completed initial sync with ECB history. Latest exchange rate date: {}",
+            report.latest_date
+        ),
+        SyncOutcome::Synced(report) => eprintln!(
+            "Synced the stale local data store with ECB. Latest exchange rate date: {}",
+            report.latest_date
+        ),
+        SyncOutcome::Failed(err) => {
+            eprintln!("warning: the local data store is stale, and syncing with ECB failed: {err}")
         }
-        Err(ConversionError::InvalidCurrency(currency)) => {
-            println!("The currency {currency} may either be invalid, or is currently not recorded by the European Central Bank.");
+        SyncOutcome::UpToDate | SyncOutcome::Throttled | SyncOutcome::Offline => (),
+    }
+
+    Ok(store)
+}
+
+/// Uses the target currencies if any, or the one in the config. Exits like
+/// any other usage error if there's neither.
+fn targets_or_default(to: Vec<iso::Currency>, config: &Config) -> Vec<iso::Currency> {
+    match (to.is_empty(), config.to()) {
+        (false, _) => to,
+        (true, Some(to)) => vec![to],
+        (true, None) => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "no target currency. Pass --to, or set a default with `moneyman config set to EUR`",
+            )
+            .exit(),
+    }
+}
+
+/// How amounts are converted
+#[derive(Clone, Copy, Debug)]
+struct ConvertOptions {
+    /// Uses the latest date in the exchange store if not specified
+    on: Option<NaiveDate>,
+    /// Whether interpolated rates may be used
+    fallback: bool,
+    /// How the converted amounts are rounded
+    rounding: Rounding,
+}
+
+impl ConvertOptions {
+    /// Exact amounts are too long for humans, so `--output human` gets the
+    /// minor units unless a rounding mode was given
+    fn for_output(self, format: OutputFormat) -> Self {
+        match (format, self.rounding.mode) {
+            (OutputFormat::Human, RoundingMode::Exact) => ConvertOptions {
+                rounding: Rounding {
+                    mode: RoundingMode::HalfEven,
+                    ..self.rounding
+                },
+                ..self
+            },
+            _ => self,
         }
-        Err(ConversionError::SameCurrency) => {
-            println!("It's 1. ONEEEEEEEEEEEEEEEEEEEEE");
+    }
+
+    fn conversion(&self) -> ConversionOptions {
+        ConversionOptions {
+            fallback: self.fallback,
+            rounding: self.rounding,
         }
     }
 }
 
-fn init_or_get_store(data_dir: PathBuf) -> ExchangeStore {
-    if !data_dir.join("eurofxref-hist.db3").exists() {
-        println!("Running initial sync with ECB...");
+/// Uses the latest date in the exchange store if there's no date
+fn date_or_latest(store: &ExchangeStore, on: Option<NaiveDate>) -> Result<NaiveDate, CliError> {
+    match on {
+        Some(date) => Ok(date),
+        None => store.get_latest_date().ok_or(CliError::NoLatestDate),
+    }
+}
 
-        let (store, latest_date) = moneyman::ExchangeStore::sync(data_dir).expect("failed ze sync");
+/// Converts an amount on a date, or the latest one. With `fallback`,
+/// interpolated rates are used as well. The converted amount is rounded.
+fn convert(
+    store: &ExchangeStore,
+    amount: Decimal,
+    from: &iso::Currency,
+    to: &iso::Currency,
+    options: &ConvertOptions,
+) -> Result<report::Conversion, CliError> {
+    let from_money = Money::from_decimal(amount, from);
+    let date = date_or_latest(store, options.on)?;
+    let to_money =
+        store.convert_on_date_with_options(from_money.clone(), to, date, &options.conversion())?;
 
-        println!(
-            "Completed initial sync with ECB history. Latest exchange rate date: {}",
-            latest_date
-        );
+    Ok(report::Conversion::new(
+        &from_money,
+        &to_money,
+        date,
+        options.fallback,
+    ))
+}
 
-        store
+/// Converts an amount to every target currency, and prints them together.
+/// A single target prints the same as it always has.
+fn print_conversions(
+    store: &ExchangeStore,
+    amount: Decimal,
+    from: &iso::Currency,
+    to: &[iso::Currency],
+    options: &ConvertOptions,
+    format: OutputFormat,
+    locale: Option<Locale>,
+) -> Result<(), CliError> {
+    let options = &options.for_output(format);
+
+    if let [to] = to {
+        output::print(&convert(store, amount, from, to, options)?, format, locale)?;
+
+        return Ok(());
+    }
+
+    let from_money = Money::from_decimal(amount, from);
+    let date = date_or_latest(store, options.on)?;
+    let targets: Vec<&iso::Currency> = to.iter().collect();
+    let results = if options.fallback {
+        store.convert_to_many_with_fallback(from_money.clone(), &targets, date)?
     } else {
-        moneyman::ExchangeStore::open(data_dir).expect("failed to open local store")
+        store.convert_to_many(from_money.clone(), &targets, date)?
+    };
+
+    // Fails on the first target that couldn't be converted, in the given order
+    let conversions = to
+        .iter()
+        .map(|to| {
+            // Every target has a result
+            let to_money = options.rounding.round(results[to.iso_alpha_code].clone()?);
+
+            Ok(report::Conversion::new(
+                &from_money,
+                &to_money,
+                date,
+                options.fallback,
+            ))
+        })
+        .collect::<Result<Vec<_>, CliError>>()?;
+
+    output::print(&report::Conversions(conversions), format, locale)?;
+
+    Ok(())
+}
+
+/// Lists every rate on a date, or the latest one, against `base`. Defaults
+/// to EUR.
+fn rates(
+    store: &ExchangeStore,
+    base: Option<iso::Currency>,
+    on: Option<NaiveDate>,
+) -> Result<report::Rates, CliError> {
+    let date = date_or_latest(store, on)?;
+    let base = base.unwrap_or(*iso::EUR);
+
+    Ok(report::Rates::from(&store.rates_on_date(&base, date)?))
+}
+
+/// Runs `find` on `period`, or each of its months, or quarters. Periods
+/// without a rate get `missing` instead, so it only fails if none of them
+/// have one.
+fn per_period<T>(
+    period: Period,
+    by: Option<Interval>,
+    find: impl Fn(Period) -> Result<T, ConversionError>,
+    missing: impl Fn(Period) -> Result<T, InvalidPeriod>,
+) -> Result<Vec<T>, CliError> {
+    let periods = match by {
+        Some(by) => by.split(&period)?,
+        None => vec![period],
+    };
+    let mut found = Vec::new();
+    let mut any_rate = false;
+
+    for period in periods {
+        found.push(match find(period) {
+            Ok(value) => {
+                any_rate = true;
+                value
+            }
+            Err(ConversionError::NoExchangeRates(_, _)) => missing(period)?,
+            Err(err) => return Err(err.into()),
+        });
     }
+
+    if !any_rate {
+        let (start, end) = period.bounds()?;
+
+        return Err(ConversionError::NoExchangeRates(start, end).into());
+    }
+
+    Ok(found)
 }
 
-fn main() {
-    let cli = Cli::parse();
+fn run(cli: Cli, commands: Option<Commands>, config: Config) -> Result<(), CliError> {
+    let data_dir = config.data_dir();
+    let format = cli.output.or(config.output).unwrap_or_default();
+    let rounding = config.rounding(cli.round, cli.precision);
 
-    let data_dir: PathBuf = dirs::home_dir()
-        .map(|home_dir| home_dir.join(".moneyman"))
-        .expect("need a home directory");
+    // Flags after a QUERY. Empty for subcommands.
+    let (words, flags) = query::take_flags(&cli.query).map_err(CliError::Query)?;
 
-    match cli.commands {
-        // If the `--on` arg is specified
+    let locale = flags.locale.or(cli.locale).or(config.locale());
+
+    match commands {
         Some(Commands::Convert {
             amount,
             from,
             to,
-            on: Some(date),
-            fallback: false,
+            on,
+            fallback,
+            no_fallback,
         }) => {
-            let from_money = Money::from_decimal(amount, &from.0);
-            let store = init_or_get_store(data_dir);
-            let to_money = store.convert_on_date(from_money.clone(), &to.0, date);
-            print_result_no_fallback(from_money, to_money, date);
+            let to = targets_or_default(to.into_iter().map(|to| to.0).collect(), &config);
+            let store = init_or_get_store(&config, cli.offline)?;
+            let options = ConvertOptions {
+                on,
+                fallback: config.fallback(fallback, no_fallback),
+                rounding,
+            };
+
+            print_conversions(&store, amount, &from.0, &to, &options, format, locale)?;
         }
-        // If the `--on` arg is not specified
-        Some(Commands::Convert {
+
+        Some(Commands::Quote {
             amount,
             from,
             to,
-            on: None,
-            fallback: false,
+            on,
+            spread,
+            fee,
+            fallback,
+            no_fallback,
         }) => {
+            let to = targets_or_default(to.into_iter().map(|to| to.0).collect(), &config)[0];
+            let store = init_or_get_store(&config, cli.offline)?;
+            let options = ConvertOptions {
+                on,
+                fallback: config.fallback(fallback, no_fallback),
+                rounding,
+            }
+            .for_output(format);
             let from_money = Money::from_decimal(amount, &from.0);
-            let store = init_or_get_store(data_dir);
+            let quote = store.quote(
+                from_money.clone(),
+                &to,
+                date_or_latest(&store, on)?,
+                &options.conversion(),
+                &pricing::pricing(spread, fee),
+            )?;
 
-            match store.get_latest_date() {
-                Some(date) => {
-                    let to_money = store.convert_on_date(from_money.clone(), &to.0, date);
-                    print_result_no_fallback(from_money, to_money, date);
-                }
-                None => {
-                    println!("Unable to fetch the latest date from the local data store. Have you tried syncing it with ECB?");
-                }
-            }
+            output::print(
+                &report::Quote::new(&quote, &from_money, options.fallback),
+                format,
+                locale,
+            )?;
         }
-        Some(Commands::Convert {
-            amount,
+
+        Some(Commands::Average {
+            period,
             from,
             to,
-            on: Some(date),
-            fallback: true,
+            by,
+            calendar_days,
         }) => {
-            let store = init_or_get_store(data_dir);
-            let from_money = Money::from_decimal(amount, &from.0);
-            let to_money = store.convert_on_date_with_fallback(from_money.clone(), &to.0, date);
-            print_result_no_fallback(from_money, to_money, date);
+            let to = targets_or_default(to.into_iter().map(|to| to.0).collect(), &config)[0];
+            let store = init_or_get_store(&config, cli.offline)?;
+            let method = match calendar_days {
+                true => AverageMethod::CalendarDays,
+                false => AverageMethod::PublishedDays,
+            };
+
+            let averages = per_period(
+                period,
+                by,
+                |period| {
+                    store
+                        .average_rate(&from.0, &to, period, method)
+                        .and_then(|average| Ok(report::Average::try_from(&average)?))
+                },
+                report::Average::missing,
+            )?;
+
+            output::print(
+                &report::Averages {
+                    from: from.0.iso_alpha_code,
+                    to: to.iso_alpha_code,
+                    method: method.name(),
+                    averages,
+                },
+                format,
+                locale,
+            )?;
         }
 
-        Some(Commands::Convert {
-            amount,
+        Some(Commands::Closing {
+            period,
             from,
             to,
-            on: None,
-            fallback: true,
+            by,
         }) => {
-            let store = init_or_get_store(data_dir);
-            let from_money = Money::from_decimal(amount, &from.0);
+            let to = targets_or_default(to.into_iter().map(|to| to.0).collect(), &config)[0];
+            let store = init_or_get_store(&config, cli.offline)?;
+            let closings = per_period(
+                period,
+                by,
+                |period| {
+                    store
+                        .closing_rate(&from.0, &to, period)
+                        .and_then(|closing| Ok(report::Closing::try_from(&closing)?))
+                },
+                report::Closing::missing,
+            )?;
 
-            match store.get_latest_date() {
-                Some(date) => {
-                    let to_money =
-                        store.convert_on_date_with_fallback(from_money.clone(), &to.0, date);
-                    print_result_no_fallback(from_money, to_money, date);
+            output::print(
+                &report::Closings {
+                    from: from.0.iso_alpha_code,
+                    to: to.iso_alpha_code,
+                    closings,
+                },
+                format,
+                locale,
+            )?;
+        }
+
+        Some(Commands::Stats { period, from, to }) => {
+            let to = targets_or_default(to.into_iter().map(|to| to.0).collect(), &config)[0];
+            let store = init_or_get_store(&config, cli.offline)?;
+            let (start, end) = period.bounds()?;
+            let stats = store.rate_stats(&from.0, &to, start, end)?;
+
+            output::print(&report::Stats::new(&from.0, &to, &stats), format, locale)?;
+        }
+
+        Some(Commands::History {
+            period,
+            from,
+            to,
+            resample,
+            sma,
+            ema,
+        }) => {
+            let to = targets_or_default(to.into_iter().map(|to| to.0).collect(), &config)[0];
+            let store = init_or_get_store(&config, cli.offline)?;
+            let history = history(
+                &store,
+                &from.0,
+                &to,
+                period,
+                resample,
+                sma.map(usize::from),
+                ema.map(usize::from),
+            )?;
+
+            output::print(&history, format, locale)?;
+        }
+
+        Some(Commands::Chart {
+            from,
+            to,
+            start,
+            end,
+            width,
+            height,
+            rebase,
+            out,
+        }) => {
+            let targets = targets_or_default(to.into_iter().map(|to| to.0).collect(), &config);
+            let store = init_or_get_store(&config, cli.offline)?;
+            let end = end.unwrap_or_else(query::today);
+
+            let mut pairs = Vec::new();
+            for to in &targets {
+                let mut rates = store.rates_between(&from.0, to, start, end)?;
+                if rates.is_empty() {
+                    return Err(ConversionError::NoExchangeRates(start, end).into());
                 }
-                None => {
-                    println!("Unable to fetch the latest date from the local data store. Have you tried syncing it with ECB?");
+                if rebase {
+                    chart::rebase(&mut rates);
                 }
+
+                pairs.push(report::ChartPair::new(&from.0, to, rates));
+            }
+            let chart = report::Chart::new(start, end, rebase, pairs, width.into(), height.into());
+
+            match out {
+                Some(path) => {
+                    chart::write(&path, &chart.svg())?;
+                    eprintln!(
+                        "Wrote a chart of {} pair(s) to {}",
+                        targets.len(),
+                        path.display()
+                    );
+                }
+                None => output::print(&chart, format, locale)?,
             }
         }
 
-        Some(Commands::Sync { force }) => {
+        Some(Commands::Info) => {
+            let store = init_or_get_store(&config, cli.offline)?;
+
+            output::print(&report::Info::from(&store.stats()?), format, locale)?;
+        }
+
+        Some(Commands::Rates { on, base }) => {
+            let store = init_or_get_store(&config, cli.offline)?;
+
+            output::print(&rates(&store, base.map(|base| base.0), on)?, format, locale)?;
+        }
+
+        Some(Commands::Matrix { on, currencies }) => {
+            let store = init_or_get_store(&config, cli.offline)?;
+            let date = date_or_latest(&store, on)?;
+            let currencies: Vec<&iso::Currency> =
+                currencies.iter().map(|currency| &currency.0).collect();
+
+            output::print(
+                &report::Matrix::from(&store.cross_rates(date, &currencies)?),
+                format,
+                locale,
+            )?;
+        }
+
+        Some(Commands::Shell) => {
+            let store = init_or_get_store(&config, cli.offline)?;
+
+            shell::run(
+                &store,
+                &data_dir.join("shell_history"),
+                shell::Settings {
+                    fallback: config.fallback(false, false),
+                    output: format,
+                    locale,
+                    rounding,
+                    to: config.to(),
+                },
+            )?;
+        }
+
+        Some(Commands::ConvertFile {
+            input,
+            out,
+            amount_col,
+            currency_col,
+            date_col,
+            to,
+            fallback,
+            no_fallback,
+        }) => {
+            let to = targets_or_default(to.into_iter().map(|to| to.0).collect(), &config)[0];
+            let fallback = config.fallback(fallback, no_fallback);
+            let store = init_or_get_store(&config, cli.offline)?;
+            let columns = convert_file::Columns {
+                amount: amount_col,
+                currency: currency_col,
+                date: date_col,
+            };
+            let reader: Box<dyn Read> = match input {
+                Some(path) if path.as_os_str() != "-" => Box::new(File::open(path)?),
+                _ => Box::new(std::io::stdin().lock()),
+            };
+            let writer: Box<dyn Write> = match out {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+
+            let summary = convert_file::convert_rows(
+                reader,
+                writer,
+                format,
+                &columns,
+                &to,
+                store.get_latest_date(),
+                |amount, currency, date| {
+                    convert_file::convert_with_store(&store, amount, currency, &to, date, fallback)
+                        .map(|converted| convert_file::Converted {
+                            amount: rounding.round_amount(converted.amount, &to),
+                            ..converted
+                        })
+                },
+            )?;
+
+            for failure in &summary.failures {
+                eprintln!("line {}: {}", failure.line, failure.message);
+            }
+
+            let failed = summary.failures.len();
+            eprintln!("Converted {} row(s), {failed} failed", summary.converted);
+
+            if failed > 0 {
+                return Err(CliError::RowsFailed {
+                    failed,
+                    total: summary.converted + failed,
+                });
+            }
+        }
+
+        Some(Commands::Sync {
+            force,
+            base_url,
+            proxy,
+            timeout,
+            retries,
+            user_agent,
+            header,
+            ca_bundle,
+            show_revisions,
+        }) => {
+            let defaults = config.sync_options();
+            let options = SyncOptions {
+                base_url: base_url.unwrap_or(defaults.base_url),
+                proxy: proxy.or(defaults.proxy),
+                timeout: timeout.map(Duration::from_secs).or(defaults.timeout),
+                retries: retries.unwrap_or(defaults.retries),
+                user_agent: user_agent.or(defaults.user_agent),
+                headers: merge_headers(defaults.headers, header),
+                ca_bundle: ca_bundle.or(defaults.ca_bundle),
+                ..defaults
+            };
+
             if force {
-                let op = std::fs::remove_file(data_dir.join("eurofxref-hist.db3"))
-                    .and_then(|_| std::fs::remove_file(data_dir.join("eurofxref-hist.csv")));
-
-                match op {
-                    Ok(_) => {
-                        println!("Deleted local data store files");
-                        println!("Syncing with ECB...");
-                        match moneyman::ExchangeStore::sync(data_dir) {
-                            Ok((_store, latest_date)) => println!(
-                                "Successfully synced with ECB. Latest exchange rate date: {}",
-                                latest_date
-                            ),
-                            Err(err) => println!("{}", err),
-                        }
+                std::fs::remove_file(data_dir.join("eurofxref-hist.db3"))
+                    .and_then(|_| std::fs::remove_file(data_dir.join("eurofxref-hist.csv")))
+                    .map_err(|_| CliError::Delete)?;
+
+                eprintln!("Deleted local data store files");
+            }
+
+            eprintln!("Syncing with ECB...");
+            let (_store, report) = moneyman::ExchangeStore::sync(data_dir, &options)?;
+
+            output::print(
+                &report::Synced::new(&report, show_revisions),
+                format,
+                locale,
+            )?;
+        }
+
+        None if !cli.query.is_empty() => {
+            let query =
+                query::parse(&words, query::today(), config.to()).map_err(CliError::Query)?;
+            let store = init_or_get_store(&config, cli.offline || flags.offline)?;
+            let options = ConvertOptions {
+                on: query.on,
+                fallback: config.fallback(
+                    cli.fallback || flags.fallback == Some(true),
+                    cli.no_fallback || flags.fallback == Some(false),
+                ),
+                rounding: config
+                    .rounding(flags.round.or(cli.round), flags.precision.or(cli.precision)),
+            };
+
+            print_conversions(
+                &store,
+                query.amount,
+                &query.from,
+                &query.to,
+                &options,
+                flags.output.unwrap_or(format),
+                locale,
+            )?;
+        }
+
+        _ => {
+            output::print_line(MONEYMAN)?;
+
+            Cli::command().print_long_help()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs the subcommands that don't need the config. `moneyman config` works
+/// on the file as a document rather than a `Config`, so a config that fails
+/// to load can still be read, and fixed.
+fn run_setup(
+    commands: SetupCommands,
+    format: OutputFormat,
+    locale: Option<Locale>,
+) -> Result<(), CliError> {
+    match commands {
+        SetupCommands::Completions { shell } => {
+            completions::generate(shell, &mut std::io::stdout().lock())?;
+        }
+
+        SetupCommands::Man { out_dir } => {
+            completions::man(
+                Cli::command(),
+                out_dir.as_deref(),
+                &mut std::io::stdout().lock(),
+            )?;
+        }
+
+        SetupCommands::Config { action } => {
+            let path = config::path();
+
+            match action {
+                ConfigAction::Get { key } => {
+                    if let Some(value) = config::get(&path, &key)? {
+                        output::print_line(value)?;
                     }
-                    Err(_) => println!("Unable to delete local data store files"),
-                }
-            } else {
-                println!("Syncing with ECB...");
-                match moneyman::ExchangeStore::sync(data_dir) {
-                    Ok((_store, latest_date)) => println!(
-                        "Successfully synced with ECB. Latest exchange rate date: {}",
-                        latest_date
-                    ),
-                    Err(err) => println!("{}", err),
                 }
+                ConfigAction::Set { key, value } => config::set(&path, &key, &value)?,
+                ConfigAction::List => output::print(
+                    &report::ConfigEntries::new(&path, config::list(&path)?),
+                    format,
+                    locale,
+                )?,
             }
         }
+    }
 
-        _ => {
-            let mut cmd = Command::new("moneyman");
+    Ok(())
+}
 
-            println!("{}", MONEYMAN);
+fn main() -> ExitCode {
+    // Answers the completion scripts, and exits, if that's what this run is
+    clap_complete::CompleteEnv::with_factory(completions::command)
+        .var(completions::COMPLETE_VAR)
+        .complete();
 
-            cmd.print_long_help().expect("Uh oh. I might've exploded.");
+    let mut cli = Cli::parse();
+    let mut format = cli.output.unwrap_or_default();
+
+    let mut run_with_config = |cli: Cli, commands| {
+        Config::load(&config::path()).and_then(|config| {
+            // The config may set the format, but it may also be what failed
+            format = cli.output.or(config.output).unwrap_or_default();
+
+            run(cli, commands, config)
+        })
+    };
+
+    let result = match cli.commands.take() {
+        // These don't need the config, so they keep working when it's broken
+        Some(AnyCommand::Setup(commands)) => run_setup(commands, format, cli.locale),
+        Some(AnyCommand::Store(commands)) => run_with_config(cli, Some(commands)),
+        None => run_with_config(cli, None),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        // Whatever reads stdout stopped early, e.g `moneyman rates | head -1`
+        Err(CliError::Io(err)) if err.kind() == std::io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.render(format));
+
+            // Exit codes are all within 0-255
+            ExitCode::from(err.exit_code() as u8)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_puts_header_flags_over_the_config() {
+        let header = |name: &str, value: &str| (name.to_string(), value.to_string());
+
+        assert_eq!(
+            vec![header("X-Team", "fx"), header("x-api-key", "from-flag")],
+            merge_headers(
+                vec![header("X-Api-Key", "from-config"), header("X-Team", "fx")],
+                vec![header("x-api-key", "from-flag")],
+            )
+        );
+    }
+
+    #[test]
+    fn it_takes_relative_dates_after_a_space() {
+        let today = query::today();
+        let week_ago = today - chrono::Days::new(7);
+
+        let cli = Cli::try_parse_from([
+            "moneyman", "convert", "100", "--from", "usd", "--to", "jpy", "--on", "-7d",
+        ])
+        .unwrap();
+        assert!(
+            matches!(cli.commands, Some(AnyCommand::Store(Commands::Convert { on: Some(on), .. })) if on == week_ago)
+        );
+
+        let cli = Cli::try_parse_from(["moneyman", "rates", "--on", "yesterday"]).unwrap();
+        assert!(
+            matches!(cli.commands, Some(AnyCommand::Store(Commands::Rates { on: Some(on), .. })) if Some(on) == today.pred_opt())
+        );
+
+        let cli = Cli::try_parse_from([
+            "moneyman", "chart", "--from", "usd", "--to", "jpy", "--start", "-2w", "--end", "-1d",
+        ])
+        .unwrap();
+        assert!(
+            matches!(cli.commands, Some(AnyCommand::Store(Commands::Chart { start, end: Some(end), .. })) if start == today - chrono::Days::new(14) && end < today)
+        );
+
+        let cli = Cli::try_parse_from([
+            "moneyman",
+            "average",
+            "-7d..today",
+            "--from",
+            "usd",
+            "--to",
+            "jpy",
+        ])
+        .unwrap();
+        assert!(
+            matches!(cli.commands, Some(AnyCommand::Store(Commands::Average { period: Period::Range { start, end }, .. })) if start == week_ago && end == today)
+        );
+    }
+}
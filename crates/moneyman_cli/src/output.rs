@@ -0,0 +1,106 @@
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::locale::Locale;
+
+/// How results are printed to stdout
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human readable text
+    #[default]
+    Plain,
+    /// Like plain, but amounts are grouped, and have currency symbols, e.g
+    /// €1.234,56. See `--locale`.
+    Human,
+    /// A single JSON document
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+}
+
+/// Anything the CLI prints as a result. JSON goes through `Serialize`, while
+/// CSV and TSV go through `columns` and `rows`.
+pub trait Report: Serialize {
+    /// Human readable text, used for `--output plain`
+    fn plain(&self) -> String;
+
+    /// Text for `--output human`, with amounts written in `locale`, or the
+    /// conventions of their currency if there's none. Same as `plain` unless
+    /// the report has amounts.
+    fn human(&self, _locale: Option<&Locale>) -> String {
+        self.plain()
+    }
+
+    /// Header row for `--output csv` and `--output tsv`
+    fn columns(&self) -> Vec<&'static str>;
+
+    /// Rows for `--output csv` and `--output tsv`, in the same order as
+    /// `columns`.
+    fn rows(&self) -> Vec<Vec<String>>;
+}
+
+/// Renders the report in the given format. `locale` is only used by
+/// `--output human`.
+pub fn render<R: Report>(report: &R, format: OutputFormat, locale: Option<Locale>) -> String {
+    match format {
+        OutputFormat::Plain => report.plain(),
+        OutputFormat::Human => report.human(locale.as_ref()),
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(report).expect("reports are always serializable")
+        }
+        OutputFormat::Csv => delimited(report, b','),
+        OutputFormat::Tsv => delimited(report, b'\t'),
+    }
+}
+
+/// Prints the report to stdout in the given format. Unlike `println!`, a
+/// closed stdout, e.g `moneyman rates | head -1`, is returned as an error.
+pub fn print<R: Report>(
+    report: &R,
+    format: OutputFormat,
+    locale: Option<Locale>,
+) -> io::Result<()> {
+    let rendered = render(report, format, locale);
+    let mut stdout = io::stdout().lock();
+
+    // CSV already ends with a newline
+    match format {
+        OutputFormat::Csv | OutputFormat::Tsv => write!(stdout, "{rendered}"),
+        OutputFormat::Plain | OutputFormat::Human | OutputFormat::Json => {
+            writeln!(stdout, "{rendered}")
+        }
+    }
+}
+
+/// Prints a line of text to stdout. Like `print`, a closed stdout is returned
+/// as an error.
+pub fn print_line(line: impl std::fmt::Display) -> io::Result<()> {
+    writeln!(io::stdout().lock(), "{line}")
+}
+
+fn delimited<R: Report>(report: &R, delimiter: u8) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(Vec::new());
+
+    writer
+        .write_record(report.columns())
+        .and_then(|_| {
+            report
+                .rows()
+                .iter()
+                .try_for_each(|row| writer.write_record(row))
+        })
+        .expect("writing to memory should not fail");
+
+    let bytes = writer
+        .into_inner()
+        .expect("writing to memory should not fail");
+
+    String::from_utf8(bytes).expect("reports are always UTF-8")
+}
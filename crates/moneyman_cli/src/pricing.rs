@@ -0,0 +1,120 @@
+use moneyman::{Fee, Pricing, Spread};
+use rust_decimal::Decimal;
+
+use crate::currency::Currency;
+
+/// A `--spread`, and the pair it's limited to, if any
+pub type SpreadArg = (Option<(&'static str, &'static str)>, Spread);
+
+fn parse_decimal(value: &str) -> Result<Decimal, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{value:?} is not a number"))
+}
+
+/// Parses `--spread` for clap, e.g `1.5%`, `150bp`, or `USD/JPY=25bp` for a
+/// single pair
+pub fn parse_spread_arg(value: &str) -> Result<SpreadArg, String> {
+    let (pair, spread) = match value.split_once('=') {
+        Some((pair, spread)) => {
+            let (from, to) = pair
+                .split_once('/')
+                .ok_or_else(|| format!("expected a pair like USD/JPY, got {pair:?}"))?;
+            let code = |code: &str| {
+                Currency::find(code.trim())
+                    .map(|currency| currency.0.iso_alpha_code)
+                    .ok_or_else(|| format!("unknown currency {code:?}"))
+            };
+
+            (Some((code(from)?, code(to)?)), spread)
+        }
+        None => (None, value),
+    };
+
+    let spread = if let Some(percent) = spread.strip_suffix('%') {
+        Spread::percent(parse_decimal(percent)?)
+    } else if let Some(basis_points) = spread.strip_suffix("bp") {
+        Spread::basis_points(parse_decimal(basis_points)?)
+    } else {
+        return Err(format!(
+            "expected a percentage, or basis points like 1.5% or 150bp, got {spread:?}"
+        ));
+    };
+
+    Ok((pair, spread))
+}
+
+/// Parses `--fee` for clap, e.g `2.50` for a fixed fee, or `0.5%`
+pub fn parse_fee_arg(value: &str) -> Result<Fee, String> {
+    match value.strip_suffix('%') {
+        Some(percent) => parse_decimal(percent).map(Fee::Percent),
+        None => parse_decimal(value).map(Fee::Fixed),
+    }
+}
+
+/// Spreads without a pair apply to every pair. The last one given wins.
+pub fn pricing(spreads: Vec<SpreadArg>, fees: Vec<Fee>) -> Pricing {
+    let mut pricing = Pricing {
+        fees,
+        ..Pricing::default()
+    };
+
+    for (pair, spread) in spreads.into_iter().rev() {
+        match pair {
+            Some(pair) => pricing.pair_spreads.push((pair, spread)),
+            None if pricing.spread == Spread::default() => pricing.spread = spread,
+            None => {}
+        }
+    }
+
+    pricing
+}
+
+#[cfg(test)]
+mod tests {
+    use rusty_money::iso;
+
+    use super::*;
+
+    #[test]
+    fn it_parses_spreads_and_fees() {
+        let hundred = Decimal::ONE_HUNDRED;
+
+        assert_eq!(
+            Ok((None, Spread::percent(Decimal::from_i128_with_scale(15, 1)))),
+            parse_spread_arg("1.5%")
+        );
+        assert_eq!(
+            Ok((Some(("USD", "JPY")), Spread::basis_points(hundred))),
+            parse_spread_arg("usd/jpy=100bp")
+        );
+        assert!(parse_spread_arg("1.5").is_err());
+        assert!(parse_spread_arg("USD-JPY=1%").is_err());
+        assert_eq!(Ok(Fee::Fixed(Decimal::from(2))), parse_fee_arg("2"));
+        assert_eq!(Ok(Fee::Percent(hundred)), parse_fee_arg("100%"));
+        assert!(parse_fee_arg("two").is_err());
+    }
+
+    #[test]
+    fn it_lets_the_last_spread_win() {
+        let pricing = pricing(
+            vec![
+                parse_spread_arg("1%").unwrap(),
+                parse_spread_arg("USD/JPY=1%").unwrap(),
+                parse_spread_arg("2%").unwrap(),
+                parse_spread_arg("USD/JPY=3%").unwrap(),
+            ],
+            Vec::new(),
+        );
+
+        assert_eq!(
+            Spread::percent(Decimal::from(3)),
+            pricing.spread_of(iso::USD, iso::JPY)
+        );
+        assert_eq!(
+            Spread::percent(Decimal::TWO),
+            pricing.spread_of(iso::USD, iso::EUR)
+        );
+    }
+}
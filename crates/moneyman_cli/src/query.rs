@@ -0,0 +1,485 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use clap::{
+    builder::{PossibleValuesParser, TypedValueParser},
+    ValueEnum,
+};
+use moneyman::{Period, RoundingMode};
+use rust_decimal::Decimal;
+use rusty_money::iso;
+
+use crate::{
+    currency::Currency,
+    locale::{parse_locale_arg, Locale},
+    output::OutputFormat,
+};
+
+/// What a date can look like, for error messages
+const DATE_FORMATS: &str =
+    "e.g 2023-05-05, today, yesterday, last friday, -7d, -2w, -3m, -1y, 2023-Q1-start, or 2023-Q1-end";
+
+/// A one-shot conversion like `100 usd jpy yesterday`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    pub amount: Decimal,
+    pub from: iso::Currency,
+    /// Every currency to convert to, in the given order
+    pub to: Vec<iso::Currency>,
+    pub on: Option<NaiveDate>,
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Parses `-7d`, `-2w`, `-3m`, or `-1y` into a date that many days, weeks,
+/// months, or years before `today`. Months, and years land on the last day
+/// of the month when it's shorter, e.g `-1m` on 03-31 is 02-28.
+fn parse_offset(date: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let offset = date.strip_prefix('-')?;
+    let (count, unit) = offset.split_at(offset.len().checked_sub(1)?);
+    let count: u32 = count.parse().ok()?;
+
+    match unit {
+        "d" => today.checked_sub_days(Days::new(count.into())),
+        "w" => today.checked_sub_days(Days::new(u64::from(count) * 7)),
+        "m" => today.checked_sub_months(Months::new(count)),
+        "y" => today.checked_sub_months(Months::new(count.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+/// Parses `2023-Q1-start`, or `2023-Q1-end` into the first, or last day of
+/// the quarter.
+fn parse_quarter(date: &str) -> Option<NaiveDate> {
+    let mut parts = date.split('-');
+    let year: i32 = parts.next()?.parse().ok()?;
+    let quarter: u32 = parts.next()?.strip_prefix('q')?.parse().ok()?;
+    let edge = parts.next()?;
+
+    if !(1..=4).contains(&quarter) || parts.next().is_some() {
+        return None;
+    }
+
+    let first_month = (quarter - 1) * 3 + 1;
+    match edge {
+        "start" => NaiveDate::from_ymd_opt(year, first_month, 1),
+        "end" => match quarter {
+            4 => NaiveDate::from_ymd_opt(year, 12, 31),
+            _ => NaiveDate::from_ymd_opt(year, first_month + 3, 1)?.pred_opt(),
+        },
+        _ => None,
+    }
+}
+
+/// Parses an absolute, or relative date. Relative dates are relative to
+/// `today`. See `DATE_FORMATS`.
+pub fn parse_date(date: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let date = date.trim().to_lowercase();
+    let words: Vec<&str> = date.split_whitespace().collect();
+
+    let parsed = match words.as_slice() {
+        ["today"] => Some(today),
+        ["yesterday"] => today.pred_opt(),
+        ["last", weekday] => parse_weekday(weekday).map(|weekday| {
+            // Strictly before today, so `last friday` on a Friday is a week ago
+            let days_back =
+                (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday() - 1)
+                    % 7
+                    + 1;
+
+            today - Days::new(days_back.into())
+        }),
+        [date] => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .or_else(|| parse_offset(date, today))
+            .or_else(|| parse_quarter(date)),
+        _ => None,
+    };
+
+    parsed.ok_or_else(|| format!("invalid date {date:?}, {DATE_FORMATS}"))
+}
+
+/// Local date, for relative dates
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// Parses `--on` for clap
+pub fn parse_date_arg(date: &str) -> Result<NaiveDate, String> {
+    parse_date(date, today())
+}
+
+/// Parses a `Period`, e.g `2023-05`, or `2023-Q2`. Either end of a range can
+/// be any date `parse_date` knows, e.g `2023-01-01..yesterday`.
+pub fn parse_period(period: &str, today: NaiveDate) -> Result<Period, String> {
+    match period.split_once("..") {
+        Some((start, end)) => {
+            let (start, end) = (parse_date(start, today)?, parse_date(end, today)?);
+
+            match start <= end {
+                true => Ok(Period::Range { start, end }),
+                false => Err(format!(
+                    "the period starts on {start}, after it ends on {end}"
+                )),
+            }
+        }
+        None => period
+            .parse()
+            .map_err(|err: moneyman::InvalidPeriod| err.to_string()),
+    }
+}
+
+/// Parses a period for clap
+pub fn parse_period_arg(period: &str) -> Result<Period, String> {
+    parse_period(period, today())
+}
+
+/// Flags given after a query. Clap can't tell them apart from relative
+/// dates like `-7d`, so they end up in the query.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrailingFlags {
+    /// `--fallback`, or `--no-fallback`, if given
+    pub fallback: Option<bool>,
+    pub output: Option<OutputFormat>,
+    pub offline: bool,
+    pub round: Option<RoundingMode>,
+    pub precision: Option<u32>,
+    pub locale: Option<Locale>,
+}
+
+/// Parses `--round` for clap, listing every mode in `--help`
+pub fn rounding_mode_parser() -> impl TypedValueParser<Value = RoundingMode> {
+    PossibleValuesParser::new(RoundingMode::ALL.map(|mode| mode.name()))
+        .map(|name| RoundingMode::from_name(&name).expect("only possible values get here"))
+}
+
+/// Takes `--fallback`, `--no-fallback`, `--offline`, `-o/--output FORMAT`,
+/// `--round MODE`, `--precision DIGITS`, and `--locale LOCALE` out of the
+/// query words
+pub fn take_flags(words: &[String]) -> Result<(Vec<&str>, TrailingFlags), String> {
+    let mut flags = TrailingFlags::default();
+    let mut rest = Vec::new();
+    let mut words = words.iter().map(String::as_str);
+
+    while let Some(word) = words.next() {
+        let (name, inline) = match word.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+            _ => (word, None),
+        };
+        let mut value = || {
+            inline
+                .or_else(|| words.next())
+                .ok_or_else(|| format!("expected a value after {name}"))
+        };
+
+        match name {
+            "-o" | "--output" | "--format" => {
+                let format = value()?;
+                flags.output = Some(
+                    OutputFormat::from_str(format, true)
+                        .map_err(|_| format!("unknown output {format:?}"))?,
+                );
+            }
+            "--round" => {
+                let mode = value()?;
+                flags.round = Some(
+                    RoundingMode::from_name(mode)
+                        .ok_or_else(|| format!("unknown rounding mode {mode:?}"))?,
+                );
+            }
+            "--precision" => {
+                let digits = value()?;
+                flags.precision = Some(
+                    digits
+                        .parse()
+                        .map_err(|_| format!("invalid precision {digits:?}"))?,
+                );
+            }
+            "--fallback" => flags.fallback = Some(true),
+            "--no-fallback" => flags.fallback = Some(false),
+            "--locale" => flags.locale = Some(parse_locale_arg(value()?)?),
+            "--offline" => flags.offline = true,
+            _ => rest.push(word),
+        }
+    }
+
+    Ok((rest, flags))
+}
+
+fn parse_currency(word: &str) -> Result<iso::Currency, String> {
+    Currency::find(word)
+        .map(|currency| currency.0)
+        .ok_or_else(|| format!("unknown currency {word:?}"))
+}
+
+/// Splits `100usd` into `100`, and `usd`. Keeps the grouping of amounts like
+/// `1,000usd` in the amount.
+fn split_amount(word: &str) -> (&str, &str) {
+    let index = word
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == ','))
+        .unwrap_or(word.len());
+
+    word.split_at(index)
+}
+
+/// Parses an amount, which may be grouped by thousands, e.g `1,000.50`
+fn parse_amount(amount: &str) -> Result<Decimal, String> {
+    let integer = amount.split('.').next().unwrap_or_default();
+    let grouped = integer.split(',').skip(1).all(|group| group.len() == 3);
+
+    match (grouped, amount.replace(',', "").parse()) {
+        (true, Ok(amount)) => Ok(amount),
+        _ => Err(format!("invalid amount {amount:?}")),
+    }
+}
+
+/// Parses a one-shot conversion, e.g `100 usd jpy`, `1,000usd in eur
+/// yesterday`, or `5 gbp to usd,jpy on 2023-Q1-end`. Relative dates are
+/// relative to `today`. Without a target currency, converts to `default_to`
+/// if there's one.
+pub fn parse(
+    words: &[&str],
+    today: NaiveDate,
+    default_to: Option<iso::Currency>,
+) -> Result<Query, String> {
+    // A quoted query, e.g `moneyman "100 usd jpy"`, is a single word
+    let words: Vec<String> = words
+        .iter()
+        .flat_map(|word| word.split_whitespace())
+        .map(str::to_lowercase)
+        .collect();
+    let mut words = words.iter().map(String::as_str);
+
+    let first = words.next().ok_or("expected an amount, e.g 100 usd jpy")?;
+    let (amount, from) = match split_amount(first) {
+        (amount, "") => (
+            amount,
+            words.next().ok_or("expected a currency after the amount")?,
+        ),
+        split => split,
+    };
+    let amount = parse_amount(amount.trim_end_matches(','))?;
+    let from = parse_currency(from.trim_matches(','))?;
+
+    // Only targets are separated by commas, e.g `usd,jpy`
+    let words: Vec<&str> = words
+        .flat_map(|word| word.split(','))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut words = words.into_iter().peekable();
+
+    words.next_if(|word| matches!(*word, "to" | "in"));
+
+    let mut to = Vec::new();
+    while let Some(currency) = words.next_if(|word| Currency::find(word).is_some()) {
+        to.push(parse_currency(currency)?);
+    }
+    if to.is_empty() {
+        to.extend(default_to);
+    }
+    if to.is_empty() {
+        return Err(match words.peek() {
+            Some(word) => format!("unknown currency {word:?}"),
+            None => "expected a currency to convert to".to_string(),
+        });
+    }
+
+    words.next_if(|word| *word == "on");
+    let date: Vec<&str> = words.collect();
+    let on = match date.as_slice() {
+        [] => None,
+        date => Some(parse_date(&date.join(" "), today)?),
+    };
+
+    Ok(Query {
+        amount,
+        from,
+        to,
+        on,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Wednesday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 5, 10).unwrap()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn it_parses_relative_dates() {
+        assert_eq!(Ok(date(2023, 5, 9)), parse_date("yesterday", today()));
+        assert_eq!(Ok(date(2023, 5, 5)), parse_date("last friday", today()));
+        assert_eq!(Ok(date(2023, 5, 3)), parse_date("Last Wed", today()));
+        assert_eq!(Ok(date(2023, 5, 3)), parse_date("-7d", today()));
+        assert_eq!(Ok(date(2023, 4, 26)), parse_date("-2w", today()));
+        assert_eq!(Ok(date(2023, 2, 10)), parse_date("-3m", today()));
+        assert_eq!(Ok(date(2022, 5, 10)), parse_date("-1y", today()));
+        assert_eq!(Ok(date(2023, 2, 28)), parse_date("-1m", date(2023, 3, 31)));
+        assert_eq!(Ok(date(2023, 2, 28)), parse_date("-1y", date(2024, 2, 29)));
+        assert!(parse_date("-1q", today()).is_err());
+        assert_eq!(Ok(date(2023, 3, 31)), parse_date("2023-Q1-end", today()));
+        assert_eq!(Ok(date(2022, 12, 31)), parse_date("2022-q4-end", today()));
+        assert_eq!(Ok(date(2023, 4, 1)), parse_date("2023-Q2-start", today()));
+        assert_eq!(Ok(date(2020, 3, 1)), parse_date("2020-03-01", today()));
+        assert!(parse_date("2023-Q5-end", today()).is_err());
+        assert!(parse_date("last month", today()).is_err());
+    }
+
+    #[test]
+    fn it_parses_periods() {
+        assert_eq!(Ok(Period::Year(2023)), parse_period("2023", today()));
+        assert_eq!(
+            Ok(Period::Range {
+                start: date(2023, 1, 1),
+                end: date(2023, 5, 9)
+            }),
+            parse_period("2023-01-01..yesterday", today())
+        );
+        assert!(parse_period("today..2023-01-01", today()).is_err());
+        assert!(parse_period("last month", today()).is_err());
+    }
+
+    #[test]
+    fn it_parses_queries() {
+        assert_eq!(
+            Ok(Query {
+                amount: Decimal::from(100),
+                from: *iso::USD,
+                to: vec![*iso::JPY],
+                on: None,
+            }),
+            parse(&["100", "usd", "jpy"], today(), None)
+        );
+        assert_eq!(
+            Ok(Query {
+                amount: Decimal::from(100),
+                from: *iso::USD,
+                to: vec![*iso::EUR],
+                on: Some(date(2023, 5, 9)),
+            }),
+            parse(&["100usd", "in", "eur", "yesterday"], today(), None)
+        );
+        assert_eq!(
+            Ok(Query {
+                amount: Decimal::from_i128_with_scale(55, 1),
+                from: *iso::GBP,
+                to: vec![*iso::USD, *iso::JPY, *iso::EUR],
+                on: Some(date(2023, 5, 5)),
+            }),
+            parse(
+                &["5.5", "GBP", "to", "USD,JPY", "eur", "on", "last", "friday"],
+                today(),
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn it_parses_grouped_amounts_and_quoted_queries() {
+        assert_eq!(
+            Ok(Query {
+                amount: Decimal::from(1000),
+                from: *iso::USD,
+                to: vec![*iso::JPY],
+                on: None,
+            }),
+            parse(&["1,000 usd jpy"], today(), None)
+        );
+        assert_eq!(
+            Ok(Query {
+                amount: Decimal::from_i128_with_scale(1234567, 1),
+                from: *iso::USD,
+                to: vec![*iso::JPY, *iso::EUR],
+                on: None,
+            }),
+            parse(&["123,456.7usd", "to", "jpy,eur"], today(), None)
+        );
+        assert!(parse(&["1,5", "usd", "jpy"], today(), None).is_err());
+    }
+
+    #[test]
+    fn it_converts_to_the_default_target_without_one() {
+        assert_eq!(
+            Ok(Query {
+                amount: Decimal::from(100),
+                from: *iso::USD,
+                to: vec![*iso::EUR],
+                on: Some(date(2023, 5, 9)),
+            }),
+            parse(&["100", "usd", "yesterday"], today(), Some(*iso::EUR))
+        );
+        assert_eq!(
+            Ok(vec![*iso::JPY]),
+            parse(&["100", "usd", "jpy"], today(), Some(*iso::EUR)).map(|query| query.to)
+        );
+    }
+
+    #[test]
+    fn it_takes_trailing_flags_out_of_queries() {
+        let words: Vec<String> = [
+            "100",
+            "usd",
+            "jpy",
+            "-7d",
+            "--fallback",
+            "-o",
+            "json",
+            "--offline",
+            "--round=floor",
+            "--precision",
+            "1",
+            "--locale",
+            "de-DE",
+        ]
+        .map(String::from)
+        .to_vec();
+
+        assert_eq!(
+            Ok((
+                vec!["100", "usd", "jpy", "-7d"],
+                TrailingFlags {
+                    fallback: Some(true),
+                    output: Some(OutputFormat::Json),
+                    offline: true,
+                    round: Some(RoundingMode::Floor),
+                    precision: Some(1),
+                    locale: Locale::find("de-DE"),
+                }
+            )),
+            take_flags(&words)
+        );
+        assert_eq!(
+            Some(false),
+            take_flags(&["--no-fallback".to_string()])
+                .unwrap()
+                .1
+                .fallback
+        );
+        assert!(take_flags(&["--output=xml".to_string()]).is_err());
+        assert!(take_flags(&["--precision".to_string()]).is_err());
+    }
+
+    #[test]
+    fn it_rejects_incomplete_queries() {
+        assert!(parse(&["100", "usd"], today(), None).is_err());
+        assert!(parse(&["usd", "jpy"], today(), None).is_err());
+        assert!(parse(&["100", "usd", "xxx"], today(), None).is_err());
+        assert!(parse(&["100", "usd", "jpy", "someday"], today(), None).is_err());
+    }
+}
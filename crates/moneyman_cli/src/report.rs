@@ -0,0 +1,1819 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use moneyman::{
+    AverageRate, ClosingRate, CrossRates, DatedRate, InvalidPeriod, Ohlc, Period, RateStats,
+    RateStatus, RatesOnDate, Revision, StoreStats, SyncReport,
+};
+use rust_decimal::Decimal;
+use rusty_money::{iso, Money};
+use serde::Serialize;
+
+use crate::{
+    chart,
+    locale::{format_money, Locale},
+    output::Report,
+};
+
+/// The result of `moneyman convert`
+#[derive(Debug, Serialize)]
+pub struct Conversion {
+    /// Amount in the source currency
+    pub amount: Decimal,
+    /// ISO alpha code of the source currency
+    pub from: &'static str,
+    /// Amount in the target currency
+    pub converted: Decimal,
+    /// ISO alpha code of the target currency
+    pub to: &'static str,
+    /// Date of the rate used
+    pub date: NaiveDate,
+    /// Whether interpolated rates were allowed
+    pub fallback: bool,
+}
+
+impl Conversion {
+    pub fn new(
+        from: &Money<'_, iso::Currency>,
+        to: &Money<'_, iso::Currency>,
+        date: NaiveDate,
+        fallback: bool,
+    ) -> Self {
+        Conversion {
+            amount: *from.amount(),
+            from: from.currency().iso_alpha_code,
+            converted: *to.amount(),
+            to: to.currency().iso_alpha_code,
+            date,
+            fallback,
+        }
+    }
+}
+
+/// Writes an amount with the symbol of its currency, or its ISO alpha code if
+/// `rusty_money` doesn't know about it.
+fn human_money(amount: Decimal, currency: &str, locale: Option<&Locale>) -> String {
+    match iso::find(currency) {
+        Some(currency) => format_money(amount, currency, locale),
+        None => format!("{amount} {currency}"),
+    }
+}
+
+impl Report for Conversion {
+    fn plain(&self) -> String {
+        format!(
+            "{} {} -> {} {} on {}",
+            self.amount, self.from, self.converted, self.to, self.date
+        )
+    }
+
+    fn human(&self, locale: Option<&Locale>) -> String {
+        format!(
+            "{} -> {} on {}",
+            human_money(self.amount, self.from, locale),
+            human_money(self.converted, self.to, locale),
+            self.date
+        )
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec!["amount", "from", "converted", "to", "date", "fallback"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.amount.to_string(),
+            self.from.to_string(),
+            self.converted.to_string(),
+            self.to.to_string(),
+            self.date.to_string(),
+            self.fallback.to_string(),
+        ]]
+    }
+}
+
+/// The result of converting to several currencies at once, e.g `--to
+/// USD,GBP`
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Conversions(pub Vec<Conversion>);
+
+impl Report for Conversions {
+    /// A table with a row per target currency, with the amounts aligned
+    fn plain(&self) -> String {
+        let Some(first) = self.0.first() else {
+            return String::new();
+        };
+        let width = self
+            .0
+            .iter()
+            .map(|conversion| conversion.converted.to_string().len())
+            .max()
+            .unwrap_or_default();
+
+        let mut lines = vec![format!("{} {} on {}", first.amount, first.from, first.date)];
+        for conversion in &self.0 {
+            lines.push(format!(
+                "\t{}  {:>width$}",
+                conversion.to, conversion.converted
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Same table as `plain`, with the amounts written in `locale`
+    fn human(&self, locale: Option<&Locale>) -> String {
+        let Some(first) = self.0.first() else {
+            return String::new();
+        };
+        let converted: Vec<String> = self
+            .0
+            .iter()
+            .map(|conversion| human_money(conversion.converted, conversion.to, locale))
+            .collect();
+        let width = converted
+            .iter()
+            .map(|amount| amount.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        let mut lines = vec![format!(
+            "{} on {}",
+            human_money(first.amount, first.from, locale),
+            first.date
+        )];
+        for (conversion, converted) in self.0.iter().zip(&converted) {
+            lines.push(format!("\t{}  {converted:>width$}", conversion.to));
+        }
+
+        lines.join("\n")
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec!["amount", "from", "converted", "to", "date", "fallback"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0.iter().flat_map(Conversion::rows).collect()
+    }
+}
+
+/// The result of `moneyman quote`
+#[derive(Debug, Serialize)]
+pub struct Quote {
+    /// Amount in the source currency, before fees
+    pub amount: Decimal,
+    /// ISO alpha code of the source currency
+    pub from: &'static str,
+    /// ISO alpha code of the target currency
+    pub to: &'static str,
+    /// Date of the rate used
+    pub date: NaiveDate,
+    /// Whether interpolated rates were allowed
+    pub fallback: bool,
+    pub mid_rate: Decimal,
+    /// The mid rate after the spread
+    pub effective_rate: Decimal,
+    /// In percent of the mid rate
+    pub spread_percent: Decimal,
+    /// Every fee, in the source currency
+    pub fees: Decimal,
+    /// The amount converted at the mid rate, without any fees
+    pub mid_amount: Decimal,
+    /// What the customer gets after fees, and the spread
+    pub customer_amount: Decimal,
+}
+
+impl Quote {
+    pub fn new(
+        quote: &moneyman::Quote<'_>,
+        amount: &Money<'_, iso::Currency>,
+        fallback: bool,
+    ) -> Self {
+        Quote {
+            amount: *amount.amount(),
+            from: amount.currency().iso_alpha_code,
+            to: quote.mid_amount.currency().iso_alpha_code,
+            date: quote.date,
+            fallback,
+            mid_rate: quote.mid_rate,
+            effective_rate: quote.effective_rate,
+            spread_percent: (quote.spread.fraction() * Decimal::ONE_HUNDRED).normalize(),
+            fees: *quote.fees.amount(),
+            mid_amount: *quote.mid_amount.amount(),
+            customer_amount: *quote.customer_amount.amount(),
+        }
+    }
+
+    /// Labelled lines, with the amounts written by `money`
+    fn lines(&self, money: impl Fn(Decimal, &str) -> String) -> String {
+        [
+            format!(
+                "{} -> {} on {}",
+                money(self.amount, self.from),
+                self.to,
+                self.date
+            ),
+            format!("\tmid rate        {}", self.mid_rate),
+            format!("\teffective rate  {}", self.effective_rate),
+            format!("\tspread          {}%", self.spread_percent),
+            format!("\tfees            {}", money(self.fees, self.from)),
+            format!("\tat mid rate     {}", money(self.mid_amount, self.to)),
+            format!("\tcustomer gets   {}", money(self.customer_amount, self.to)),
+        ]
+        .join("\n")
+    }
+}
+
+impl Report for Quote {
+    fn plain(&self) -> String {
+        self.lines(|amount, currency| format!("{amount} {currency}"))
+    }
+
+    fn human(&self, locale: Option<&Locale>) -> String {
+        self.lines(|amount, currency| human_money(amount, currency, locale))
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec![
+            "amount",
+            "from",
+            "to",
+            "date",
+            "fallback",
+            "mid_rate",
+            "effective_rate",
+            "spread_percent",
+            "fees",
+            "mid_amount",
+            "customer_amount",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.amount.to_string(),
+            self.from.to_string(),
+            self.to.to_string(),
+            self.date.to_string(),
+            self.fallback.to_string(),
+            self.mid_rate.to_string(),
+            self.effective_rate.to_string(),
+            self.spread_percent.to_string(),
+            self.fees.to_string(),
+            self.mid_amount.to_string(),
+            self.customer_amount.to_string(),
+        ]]
+    }
+}
+
+/// The result of `moneyman average`
+#[derive(Debug, Serialize)]
+pub struct Averages {
+    /// ISO alpha code of the source currency
+    pub from: &'static str,
+    /// ISO alpha code of the target currency
+    pub to: &'static str,
+    /// Either `published-days`, or `calendar-days`
+    pub method: &'static str,
+    /// A single period, or one per month, or quarter with `--by`
+    pub averages: Vec<Average>,
+}
+
+/// The average rate over a period
+#[derive(Debug, Serialize)]
+pub struct Average {
+    /// e.g `2023-05`, `2023-Q2`, or `2023-04-06..2024-04-05`
+    pub period: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// `None` if there's no rate in the period
+    pub rate: Option<Decimal>,
+    /// How many days were averaged
+    pub days: usize,
+    /// How many of `days` were interpolated
+    pub interpolated_days: usize,
+}
+
+impl Average {
+    /// A period without a single rate
+    pub fn missing(period: Period) -> Result<Self, InvalidPeriod> {
+        let (start, end) = period.bounds()?;
+
+        Ok(Average {
+            period: period.to_string(),
+            start,
+            end,
+            rate: None,
+            days: 0,
+            interpolated_days: 0,
+        })
+    }
+}
+
+impl TryFrom<&AverageRate> for Average {
+    type Error = InvalidPeriod;
+
+    fn try_from(average: &AverageRate) -> Result<Self, Self::Error> {
+        Ok(Average {
+            rate: Some(average.rate),
+            days: average.days,
+            interpolated_days: average.interpolated_days,
+            ..Average::missing(average.period)?
+        })
+    }
+}
+
+impl Report for Averages {
+    /// A row per period, with the rates aligned, and rounded to 6 decimal
+    /// places. The other formats keep them exact.
+    fn plain(&self) -> String {
+        let rates: Vec<String> = self
+            .averages
+            .iter()
+            .map(|average| {
+                average
+                    .rate
+                    .map_or("-".to_string(), |rate| rate.round_dp(6).to_string())
+            })
+            .collect();
+        let period_width = self
+            .averages
+            .iter()
+            .map(|average| average.period.len())
+            .max()
+            .unwrap_or_default();
+        let rate_width = rates.iter().map(String::len).max().unwrap_or_default();
+
+        let mut lines = vec![format!(
+            "{} -> {}, averaged over {}",
+            self.from,
+            self.to,
+            self.method.replace('-', " ")
+        )];
+        for (average, rate) in self.averages.iter().zip(&rates) {
+            let days = match average.interpolated_days {
+                0 => format!("{} days", average.days),
+                interpolated => format!("{} days, {interpolated} interpolated", average.days),
+            };
+
+            lines.push(format!(
+                "\t{:<period_width$}  {rate:>rate_width$}  {days}",
+                average.period
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec![
+            "from",
+            "to",
+            "period",
+            "start",
+            "end",
+            "method",
+            "rate",
+            "days",
+            "interpolated_days",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.averages
+            .iter()
+            .map(|average| {
+                vec![
+                    self.from.to_string(),
+                    self.to.to_string(),
+                    average.period.clone(),
+                    average.start.to_string(),
+                    average.end.to_string(),
+                    self.method.to_string(),
+                    average
+                        .rate
+                        .map(|rate| rate.to_string())
+                        .unwrap_or_default(),
+                    average.days.to_string(),
+                    average.interpolated_days.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// The result of `moneyman closing`
+#[derive(Debug, Serialize)]
+pub struct Closings {
+    /// ISO alpha code of the source currency
+    pub from: &'static str,
+    /// ISO alpha code of the target currency
+    pub to: &'static str,
+    /// A single period, or one per month, or quarter with `--by`
+    pub closings: Vec<Closing>,
+}
+
+/// The closing rate of a period
+#[derive(Debug, Serialize)]
+pub struct Closing {
+    /// e.g `2023-05`, `2023-Q2`, or `2023-04-06..2024-04-05`
+    pub period: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// The date the rate was published on. `None` if there's no rate in the
+    /// period.
+    pub date: Option<NaiveDate>,
+    pub rate: Option<Decimal>,
+}
+
+impl Closing {
+    /// A period without a single rate
+    pub fn missing(period: Period) -> Result<Self, InvalidPeriod> {
+        let (start, end) = period.bounds()?;
+
+        Ok(Closing {
+            period: period.to_string(),
+            start,
+            end,
+            date: None,
+            rate: None,
+        })
+    }
+}
+
+impl TryFrom<&ClosingRate> for Closing {
+    type Error = InvalidPeriod;
+
+    fn try_from(closing: &ClosingRate) -> Result<Self, Self::Error> {
+        Ok(Closing {
+            date: Some(closing.date),
+            rate: Some(closing.rate),
+            ..Closing::missing(closing.period)?
+        })
+    }
+}
+
+impl Report for Closings {
+    /// A row per period, with the date of each rate. Rates are rounded to 6
+    /// decimal places, like `Averages`.
+    fn plain(&self) -> String {
+        let rates: Vec<String> = self
+            .closings
+            .iter()
+            .map(|closing| {
+                closing
+                    .rate
+                    .map_or("-".to_string(), |rate| rate.round_dp(6).to_string())
+            })
+            .collect();
+        let period_width = self
+            .closings
+            .iter()
+            .map(|closing| closing.period.len())
+            .max()
+            .unwrap_or_default();
+        let rate_width = rates.iter().map(String::len).max().unwrap_or_default();
+
+        let mut lines = vec![format!("{} -> {}, closing rates", self.from, self.to)];
+        for (closing, rate) in self.closings.iter().zip(&rates) {
+            let line = format!("\t{:<period_width$}  {rate:>rate_width$}", closing.period);
+
+            lines.push(match closing.date {
+                Some(date) => format!("{line}  on {date}"),
+                None => line,
+            });
+        }
+
+        lines.join("\n")
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec!["from", "to", "period", "start", "end", "date", "rate"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.closings
+            .iter()
+            .map(|closing| {
+                vec![
+                    self.from.to_string(),
+                    self.to.to_string(),
+                    closing.period.clone(),
+                    closing.start.to_string(),
+                    closing.end.to_string(),
+                    closing
+                        .date
+                        .map(|date| date.to_string())
+                        .unwrap_or_default(),
+                    closing
+                        .rate
+                        .map(|rate| rate.to_string())
+                        .unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// The result of `moneyman stats`
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    /// ISO alpha code of the source currency
+    pub from: &'static str,
+    /// ISO alpha code of the target currency
+    pub to: &'static str,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// How many published rates there are in the period
+    pub observations: usize,
+    pub first: StatsRate,
+    pub last: StatsRate,
+    pub min: StatsRate,
+    pub max: StatsRate,
+    pub mean: Decimal,
+    pub std_dev: Option<Decimal>,
+    /// Annualized volatility of the daily log returns, in percent
+    pub volatility_percent: Option<Decimal>,
+    pub change_percent: Decimal,
+}
+
+/// A rate, and the date it was published on
+#[derive(Debug, Serialize)]
+pub struct StatsRate {
+    pub date: NaiveDate,
+    pub rate: Decimal,
+}
+
+impl From<&DatedRate> for StatsRate {
+    fn from(rate: &DatedRate) -> Self {
+        StatsRate {
+            date: rate.date,
+            rate: rate.rate,
+        }
+    }
+}
+
+impl Stats {
+    pub fn new(from: &iso::Currency, to: &iso::Currency, stats: &RateStats) -> Self {
+        Stats {
+            from: from.iso_alpha_code,
+            to: to.iso_alpha_code,
+            start: stats.start,
+            end: stats.end,
+            observations: stats.observations,
+            first: StatsRate::from(&stats.first),
+            last: StatsRate::from(&stats.last),
+            min: StatsRate::from(&stats.min),
+            max: StatsRate::from(&stats.max),
+            mean: stats.mean,
+            std_dev: stats.std_dev,
+            volatility_percent: stats
+                .volatility
+                .map(|volatility| volatility * Decimal::ONE_HUNDRED),
+            change_percent: stats.change_percent,
+        }
+    }
+}
+
+impl Report for Stats {
+    /// Labelled lines. Rates are rounded to 6 decimal places, and percentages
+    /// to 2, while the other formats keep them exact.
+    fn plain(&self) -> String {
+        let rate = |rate: &StatsRate| format!("{}  on {}", rate.rate.round_dp(6), rate.date);
+
+        [
+            format!(
+                "{} -> {} from {} to {}, {} published rates",
+                self.from, self.to, self.start, self.end, self.observations
+            ),
+            format!("\tfirst       {}", rate(&self.first)),
+            format!("\tlast        {}", rate(&self.last)),
+            format!("\tmin         {}", rate(&self.min)),
+            format!("\tmax         {}", rate(&self.max)),
+            format!("\tmean        {}", self.mean.round_dp(6)),
+            format!(
+                "\tstd dev     {}",
+                self.std_dev
+                    .map_or("-".to_string(), |std_dev| std_dev.round_dp(6).to_string())
+            ),
+            format!(
+                "\tvolatility  {}",
+                self.volatility_percent
+                    .map_or("-".to_string(), |volatility| format!(
+                        "{}% a year",
+                        volatility.round_dp(2)
+                    ))
+            ),
+            format!("\tchange      {:+}%", self.change_percent.round_dp(2)),
+        ]
+        .join("\n")
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec![
+            "from",
+            "to",
+            "start",
+            "end",
+            "observations",
+            "first_date",
+            "first_rate",
+            "last_date",
+            "last_rate",
+            "min_date",
+            "min_rate",
+            "max_date",
+            "max_rate",
+            "mean",
+            "std_dev",
+            "volatility_percent",
+            "change_percent",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let optional =
+            |value: Option<Decimal>| value.map(|value| value.to_string()).unwrap_or_default();
+
+        vec![vec![
+            self.from.to_string(),
+            self.to.to_string(),
+            self.start.to_string(),
+            self.end.to_string(),
+            self.observations.to_string(),
+            self.first.date.to_string(),
+            self.first.rate.to_string(),
+            self.last.date.to_string(),
+            self.last.rate.to_string(),
+            self.min.date.to_string(),
+            self.min.rate.to_string(),
+            self.max.date.to_string(),
+            self.max.rate.to_string(),
+            self.mean.to_string(),
+            optional(self.std_dev),
+            optional(self.volatility_percent),
+            self.change_percent.to_string(),
+        ]]
+    }
+}
+
+/// The result of `moneyman history`
+#[derive(Debug, Serialize)]
+pub struct History {
+    /// ISO alpha code of the source currency
+    pub from: &'static str,
+    /// ISO alpha code of the target currency
+    pub to: &'static str,
+    /// Either `daily`, `weekly`, or `monthly`
+    pub resample: &'static str,
+    /// Window of the simple moving average, if requested
+    pub sma: Option<usize>,
+    /// Window of the exponential moving average, if requested
+    pub ema: Option<usize>,
+    pub points: Vec<HistoryPoint>,
+}
+
+/// A day, week, or month of `History`. Days have the same open, high, low,
+/// and close rates.
+#[derive(Debug, Serialize)]
+pub struct HistoryPoint {
+    /// The day, or the first day of the week, or month
+    pub date: NaiveDate,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// How many published rates went into it
+    pub observations: usize,
+    /// Moving averages of the closing rates. `None` until there are enough
+    /// points, or if not requested.
+    pub sma: Option<Decimal>,
+    pub ema: Option<Decimal>,
+}
+
+impl From<&Ohlc> for HistoryPoint {
+    fn from(ohlc: &Ohlc) -> Self {
+        HistoryPoint {
+            date: ohlc.start,
+            open: ohlc.open,
+            high: ohlc.high,
+            low: ohlc.low,
+            close: ohlc.close,
+            observations: ohlc.observations,
+            sma: None,
+            ema: None,
+        }
+    }
+}
+
+/// Aligns the cells of `rows` into lines. The first column is aligned to the
+/// left, and the rest to the right.
+fn table(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.first().map_or(0, Vec::len);
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(column, (cell, width))| match column {
+                    0 => format!("{cell:<width$}"),
+                    _ => format!("{cell:>width$}"),
+                })
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+impl Report for History {
+    /// A table with a row per point. Days only have their rate, while weeks,
+    /// and months have all of their OHLC rates. Rates are rounded to 6
+    /// decimal places.
+    fn plain(&self) -> String {
+        let rate = |rate: Decimal| rate.round_dp(6).to_string();
+        let optional = |rate: Option<Decimal>| {
+            rate.map_or("-".to_string(), |rate| rate.round_dp(6).to_string())
+        };
+        let daily = self.resample == "daily";
+
+        let mut header = match daily {
+            true => vec!["date".to_string(), "rate".to_string()],
+            false => ["date", "open", "high", "low", "close"]
+                .map(String::from)
+                .to_vec(),
+        };
+        header.extend(self.sma.map(|window| format!("sma({window})")));
+        header.extend(self.ema.map(|window| format!("ema({window})")));
+
+        let mut rows = vec![header];
+        for point in &self.points {
+            let mut row = match daily {
+                true => vec![point.date.to_string(), rate(point.close)],
+                false => vec![
+                    point.date.to_string(),
+                    rate(point.open),
+                    rate(point.high),
+                    rate(point.low),
+                    rate(point.close),
+                ],
+            };
+            if self.sma.is_some() {
+                row.push(optional(point.sma));
+            }
+            if self.ema.is_some() {
+                row.push(optional(point.ema));
+            }
+
+            rows.push(row);
+        }
+
+        let mut lines = vec![format!("{} -> {}, {}", self.from, self.to, self.resample)];
+        lines.extend(table(&rows).into_iter().map(|line| format!("\t{line}")));
+
+        lines.join("\n")
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec![
+            "date",
+            "open",
+            "high",
+            "low",
+            "close",
+            "observations",
+            "sma",
+            "ema",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let optional =
+            |rate: Option<Decimal>| rate.map(|rate| rate.to_string()).unwrap_or_default();
+
+        self.points
+            .iter()
+            .map(|point| {
+                vec![
+                    point.date.to_string(),
+                    point.open.to_string(),
+                    point.high.to_string(),
+                    point.low.to_string(),
+                    point.close.to_string(),
+                    point.observations.to_string(),
+                    optional(point.sma),
+                    optional(point.ema),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// The result of `moneyman chart`
+#[derive(Debug, Serialize)]
+pub struct Chart {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Whether every pair was rescaled to start at 100
+    pub rebased: bool,
+    pub pairs: Vec<ChartPair>,
+    /// Size of the plain chart in characters
+    #[serde(skip)]
+    width: usize,
+    #[serde(skip)]
+    height: usize,
+}
+
+/// A currency pair on the chart
+#[derive(Debug, Serialize)]
+pub struct ChartPair {
+    /// ISO alpha code of the source currency
+    pub from: &'static str,
+    /// ISO alpha code of the target currency
+    pub to: &'static str,
+    pub rates: Vec<ChartRate>,
+    #[serde(skip)]
+    series: Vec<DatedRate>,
+}
+
+impl ChartPair {
+    pub fn new(from: &iso::Currency, to: &iso::Currency, series: Vec<DatedRate>) -> Self {
+        ChartPair {
+            from: from.iso_alpha_code,
+            to: to.iso_alpha_code,
+            rates: series.iter().map(ChartRate::from).collect(),
+            series,
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("{} -> {}", self.from, self.to)
+    }
+}
+
+impl Chart {
+    pub fn new(
+        start: NaiveDate,
+        end: NaiveDate,
+        rebased: bool,
+        pairs: Vec<ChartPair>,
+        width: usize,
+        height: usize,
+    ) -> Self {
+        Chart {
+            start,
+            end,
+            rebased,
+            pairs,
+            width,
+            height,
+        }
+    }
+
+    /// The chart as an SVG image, for `--out`
+    pub fn svg(&self) -> String {
+        let labels: Vec<String> = self.pairs.iter().map(ChartPair::label).collect();
+        let title = match self.rebased {
+            true => format!("{}, rebased to 100", labels.join(", ")),
+            false => labels.join(", "),
+        };
+        let lines: Vec<chart::Line> = self
+            .pairs
+            .iter()
+            .zip(labels)
+            .map(|(pair, label)| chart::Line {
+                label,
+                rates: &pair.series,
+            })
+            .collect();
+
+        chart::svg(&title, &lines, self.start, self.end)
+    }
+}
+
+/// A rate on the chart, either `observed`, or `interpolated`
+#[derive(Debug, Serialize)]
+pub struct ChartRate {
+    pub date: NaiveDate,
+    pub rate: Decimal,
+    pub status: &'static str,
+}
+
+impl From<&DatedRate> for ChartRate {
+    fn from(rate: &DatedRate) -> Self {
+        ChartRate {
+            date: rate.date,
+            rate: rate.rate,
+            status: match rate.status {
+                RateStatus::Observed => "observed",
+                RateStatus::Interpolated => "interpolated",
+                RateStatus::Missing => "missing",
+            },
+        }
+    }
+}
+
+impl Report for Chart {
+    /// A braille line chart per pair, while the other formats list the rates
+    fn plain(&self) -> String {
+        self.pairs
+            .iter()
+            .map(|pair| {
+                let interpolated = pair
+                    .series
+                    .iter()
+                    .filter(|rate| rate.status == RateStatus::Interpolated)
+                    .count();
+                let rebased = match self.rebased {
+                    true => " rebased to 100",
+                    false => "",
+                };
+
+                let mut lines = vec![format!(
+                    "{}{rebased}, {} published, {interpolated} interpolated rates",
+                    pair.label(),
+                    pair.series.len() - interpolated
+                )];
+                lines.extend(chart::braille(&pair.series, self.width, self.height));
+
+                lines.join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec!["from", "to", "date", "rate", "status"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.pairs
+            .iter()
+            .flat_map(|pair| {
+                pair.rates.iter().map(|rate| {
+                    vec![
+                        pair.from.to_string(),
+                        pair.to.to_string(),
+                        rate.date.to_string(),
+                        rate.rate.to_string(),
+                        rate.status.to_string(),
+                    ]
+                })
+            })
+            .collect()
+    }
+}
+
+/// The result of `moneyman sync`
+#[derive(Debug, Serialize)]
+pub struct Synced {
+    /// The latest date with exchange rates
+    pub latest_date: NaiveDate,
+    /// How many already stored rates ECB corrected
+    pub revision_count: usize,
+    /// The corrected rates. Only listed with `--show-revisions`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revisions: Option<Vec<RevisedRate>>,
+}
+
+/// A rate ECB corrected
+#[derive(Debug, Serialize)]
+pub struct RevisedRate {
+    pub date: NaiveDate,
+    pub currency: String,
+    pub old: Option<Decimal>,
+    pub new: Option<Decimal>,
+}
+
+impl From<&Revision> for RevisedRate {
+    fn from(revision: &Revision) -> Self {
+        RevisedRate {
+            date: revision.date,
+            currency: revision.currency.clone(),
+            old: revision.old,
+            new: revision.new,
+        }
+    }
+}
+
+impl Synced {
+    pub fn new(report: &SyncReport, show_revisions: bool) -> Self {
+        Synced {
+            latest_date: report.latest_date,
+            revision_count: report.revisions.len(),
+            revisions: show_revisions
+                .then(|| report.revisions.iter().map(RevisedRate::from).collect()),
+        }
+    }
+}
+
+/// Shows a missing rate as `N/A`, like ECB does
+fn rate_or_na(rate: Option<Decimal>) -> String {
+    rate.map(|rate| rate.to_string())
+        .unwrap_or_else(|| String::from("N/A"))
+}
+
+impl Report for Synced {
+    fn plain(&self) -> String {
+        let mut lines = vec![format!(
+            "Successfully synced with ECB. Latest exchange rate date: {}",
+            self.latest_date
+        )];
+
+        if self.revision_count > 0 {
+            lines.push(format!(
+                "ECB revised {} previously synced rate(s).",
+                self.revision_count
+            ));
+        }
+
+        for revision in self.revisions.iter().flatten() {
+            lines.push(format!(
+                "\t{} {}: {} -> {}",
+                revision.date,
+                revision.currency,
+                rate_or_na(revision.old),
+                rate_or_na(revision.new)
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    /// With `--show-revisions`, CSV and TSV list the revisions. Otherwise it's
+    /// a one row summary.
+    fn columns(&self) -> Vec<&'static str> {
+        match self.revisions {
+            Some(_) => vec!["date", "currency", "old", "new"],
+            None => vec!["latest_date", "revision_count"],
+        }
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        match &self.revisions {
+            Some(revisions) => revisions
+                .iter()
+                .map(|revision| {
+                    vec![
+                        revision.date.to_string(),
+                        revision.currency.clone(),
+                        rate_or_na(revision.old),
+                        rate_or_na(revision.new),
+                    ]
+                })
+                .collect(),
+            None => vec![vec![
+                self.latest_date.to_string(),
+                self.revision_count.to_string(),
+            ]],
+        }
+    }
+}
+
+/// The result of `moneyman rates`
+#[derive(Debug, Serialize)]
+pub struct Rates {
+    pub date: NaiveDate,
+    /// ISO alpha code of the currency every rate is quoted against
+    pub base: &'static str,
+    /// Whether the rates on this date were interpolated
+    pub interpolated: bool,
+    pub rates: Vec<QuotedRate>,
+}
+
+/// One unit of the base currency in another currency
+#[derive(Debug, Serialize)]
+pub struct QuotedRate {
+    pub currency: String,
+    pub rate: Option<Decimal>,
+    /// `observed`, `interpolated`, or `missing`
+    pub status: &'static str,
+}
+
+impl From<&RatesOnDate> for Rates {
+    fn from(rates: &RatesOnDate) -> Self {
+        Rates {
+            date: rates.date,
+            base: rates.base,
+            interpolated: rates
+                .rates
+                .iter()
+                .any(|rate| rate.status == RateStatus::Interpolated),
+            rates: rates
+                .rates
+                .iter()
+                .map(|rate| QuotedRate {
+                    currency: rate.currency.clone(),
+                    rate: rate.rate,
+                    status: match rate.status {
+                        RateStatus::Observed => "observed",
+                        RateStatus::Interpolated => "interpolated",
+                        RateStatus::Missing => "missing",
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Report for Rates {
+    fn plain(&self) -> String {
+        let mut lines = vec![format!(
+            "1 {} on {}{}",
+            self.base,
+            self.date,
+            if self.interpolated {
+                " (interpolated)"
+            } else {
+                ""
+            }
+        )];
+
+        for rate in &self.rates {
+            let note = match rate.status {
+                "observed" => "",
+                status => status,
+            };
+
+            lines.push(format!(
+                "\t{}  {:<32}{}",
+                rate.currency,
+                rate_or_na(rate.rate),
+                note
+            ));
+        }
+
+        lines.join("\n").trim_end().to_string()
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec!["currency", "rate", "status"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.rates
+            .iter()
+            .map(|rate| {
+                vec![
+                    rate.currency.clone(),
+                    rate_or_na(rate.rate),
+                    rate.status.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// The result of `moneyman matrix`
+#[derive(Debug, Serialize)]
+pub struct Matrix {
+    pub date: NaiveDate,
+    /// Whether the rates on this date were interpolated
+    pub interpolated: bool,
+    /// ISO alpha codes of the currencies, in the order they were given
+    pub currencies: Vec<&'static str>,
+    /// `rates[i][j]` is the rate of one unit of `currencies[i]` in
+    /// `currencies[j]`. `null` if either has no rate on the date.
+    pub rates: Vec<Vec<Option<Decimal>>>,
+}
+
+impl From<&CrossRates> for Matrix {
+    fn from(cross: &CrossRates) -> Self {
+        Matrix {
+            date: cross.date,
+            interpolated: cross.status == RateStatus::Interpolated,
+            currencies: cross.currencies.clone(),
+            rates: cross.rates.clone(),
+        }
+    }
+}
+
+impl Report for Matrix {
+    /// A table of one unit of each row's currency in each column's. Rates are
+    /// rounded to 6 decimal places.
+    fn plain(&self) -> String {
+        let mut rows = vec![std::iter::once("")
+            .chain(self.currencies.iter().copied())
+            .map(String::from)
+            .collect()];
+        for (currency, rates) in self.currencies.iter().zip(&self.rates) {
+            rows.push(
+                std::iter::once(currency.to_string())
+                    .chain(
+                        rates
+                            .iter()
+                            .map(|rate| rate_or_na(rate.map(|rate| rate.round_dp(6)))),
+                    )
+                    .collect(),
+            );
+        }
+
+        let mut lines = vec![format!(
+            "1 unit of each row in each column on {}{}",
+            self.date,
+            if self.interpolated {
+                " (interpolated)"
+            } else {
+                ""
+            }
+        )];
+        lines.extend(table(&rows).into_iter().map(|line| format!("\t{line}")));
+
+        lines.join("\n")
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        std::iter::once("currency")
+            .chain(self.currencies.iter().copied())
+            .collect()
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.currencies
+            .iter()
+            .zip(&self.rates)
+            .map(|(currency, rates)| {
+                std::iter::once(currency.to_string())
+                    .chain(rates.iter().map(|rate| rate_or_na(*rate)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// The result of `moneyman info`
+#[derive(Debug, Serialize)]
+pub struct Info {
+    pub data_dir: PathBuf,
+    /// Size of the SQLite database in bytes
+    pub db_size: u64,
+    pub first_date: Option<NaiveDate>,
+    pub latest_date: Option<NaiveDate>,
+    pub observed_rows: usize,
+    pub interpolated_rows: usize,
+    pub currencies: Vec<Coverage>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub source_url: Option<String>,
+}
+
+/// The first and last dates ECB published a rate for a currency
+#[derive(Debug, Serialize)]
+pub struct Coverage {
+    pub currency: String,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+}
+
+impl From<&StoreStats> for Info {
+    fn from(stats: &StoreStats) -> Self {
+        Info {
+            data_dir: stats.data_dir.clone(),
+            db_size: stats.db_size,
+            first_date: stats.first_date,
+            latest_date: stats.latest_date,
+            observed_rows: stats.observed_rows,
+            interpolated_rows: stats.interpolated_rows,
+            currencies: stats
+                .currencies
+                .iter()
+                .map(|coverage| Coverage {
+                    currency: coverage.currency.clone(),
+                    first_date: coverage.first_date,
+                    last_date: coverage.last_date,
+                })
+                .collect(),
+            last_synced_at: stats.last_synced_at,
+            source_url: stats.source_url.clone(),
+        }
+    }
+}
+
+/// Shows a size in bytes with the largest unit that keeps it above 1
+fn human_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", units[unit]),
+    }
+}
+
+fn or_unknown<T: ToString>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map(T::to_string)
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+impl Report for Info {
+    fn plain(&self) -> String {
+        let mut lines = vec![
+            format!("Data directory:     {}", self.data_dir.display()),
+            format!("Database size:      {}", human_size(self.db_size)),
+            format!("First date:         {}", or_unknown(&self.first_date)),
+            format!("Latest date:        {}", or_unknown(&self.latest_date)),
+            format!("Observed rows:      {}", self.observed_rows),
+            format!("Interpolated rows:  {}", self.interpolated_rows),
+            format!(
+                "Last synced at:     {}",
+                or_unknown(
+                    &self
+                        .last_synced_at
+                        .map(|synced_at| synced_at.format("%Y-%m-%d %H:%M:%S UTC"))
+                )
+            ),
+            format!("Source:             {}", or_unknown(&self.source_url)),
+            format!("Currencies:         {}", self.currencies.len()),
+        ];
+
+        for coverage in &self.currencies {
+            lines.push(format!(
+                "\t{}  {} to {}",
+                coverage.currency, coverage.first_date, coverage.last_date
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    /// CSV and TSV list the currencies, since the rest doesn't fit in rows
+    fn columns(&self) -> Vec<&'static str> {
+        vec!["currency", "first_date", "last_date"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.currencies
+            .iter()
+            .map(|coverage| {
+                vec![
+                    coverage.currency.clone(),
+                    coverage.first_date.to_string(),
+                    coverage.last_date.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// The result of `moneyman config list`
+#[derive(Debug, Serialize)]
+pub struct ConfigEntries {
+    pub path: PathBuf,
+    pub entries: Vec<ConfigEntry>,
+}
+
+/// A key in the config file, and its value if set
+#[derive(Debug, Serialize)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl ConfigEntries {
+    pub fn new(path: &Path, entries: Vec<(String, Option<String>)>) -> Self {
+        ConfigEntries {
+            path: path.to_path_buf(),
+            entries: entries
+                .into_iter()
+                .map(|(key, value)| ConfigEntry { key, value })
+                .collect(),
+        }
+    }
+}
+
+impl Report for ConfigEntries {
+    fn plain(&self) -> String {
+        let width = self
+            .entries
+            .iter()
+            .map(|entry| entry.key.len())
+            .max()
+            .unwrap_or_default();
+        let mut lines = vec![format!("# {}", self.path.display())];
+
+        for entry in &self.entries {
+            lines.push(format!(
+                "{:width$} = {}",
+                entry.key,
+                entry.value.as_deref().unwrap_or("(unset)")
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    fn columns(&self) -> Vec<&'static str> {
+        vec!["key", "value"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.entries
+            .iter()
+            .map(|entry| vec![entry.key.clone(), entry.value.clone().unwrap_or_default()])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{render, OutputFormat};
+
+    fn conversion() -> Conversion {
+        Conversion {
+            amount: Decimal::from(50),
+            from: "EUR",
+            converted: Decimal::from_i128_with_scale(304458, 2),
+            to: "PHP",
+            date: NaiveDate::from_ymd_opt(2023, 5, 6).unwrap(),
+            fallback: true,
+        }
+    }
+
+    fn synced() -> Synced {
+        Synced {
+            latest_date: NaiveDate::from_ymd_opt(2023, 5, 12).unwrap(),
+            revision_count: 1,
+            revisions: Some(vec![RevisedRate {
+                date: NaiveDate::from_ymd_opt(2023, 5, 5).unwrap(),
+                currency: "USD".to_string(),
+                old: Some(Decimal::from_i128_with_scale(11014, 4)),
+                new: None,
+            }]),
+        }
+    }
+
+    fn rates() -> Rates {
+        Rates {
+            date: NaiveDate::from_ymd_opt(2023, 5, 6).unwrap(),
+            base: "USD",
+            interpolated: true,
+            rates: vec![
+                QuotedRate {
+                    currency: "CYP".to_string(),
+                    rate: None,
+                    status: "missing",
+                },
+                QuotedRate {
+                    currency: "EUR".to_string(),
+                    rate: Some(Decimal::from_i128_with_scale(9070, 4)),
+                    status: "interpolated",
+                },
+            ],
+        }
+    }
+
+    fn info() -> Info {
+        Info {
+            data_dir: PathBuf::from("/home/moneyman/.moneyman"),
+            db_size: 5_452_595,
+            first_date: NaiveDate::from_ymd_opt(1999, 1, 4),
+            latest_date: NaiveDate::from_ymd_opt(2023, 5, 12),
+            observed_rows: 6218,
+            interpolated_rows: 2642,
+            currencies: vec![Coverage {
+                currency: "CYP".to_string(),
+                first_date: NaiveDate::from_ymd_opt(1999, 1, 4).unwrap(),
+                last_date: NaiveDate::from_ymd_opt(2007, 12, 31).unwrap(),
+            }],
+            last_synced_at: None,
+            source_url: Some(
+                "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-hist.zip".to_string(),
+            ),
+        }
+    }
+
+    #[test]
+    fn it_renders_conversions() {
+        insta::assert_snapshot!(
+            "conversion_json",
+            render(&conversion(), OutputFormat::Json, None)
+        );
+        insta::assert_snapshot!(
+            "conversion_csv",
+            render(&conversion(), OutputFormat::Csv, None)
+        );
+        insta::assert_snapshot!(
+            "conversion_tsv",
+            render(&conversion(), OutputFormat::Tsv, None)
+        );
+        insta::assert_snapshot!(
+            "conversion_plain",
+            render(&conversion(), OutputFormat::Plain, None)
+        );
+    }
+
+    #[test]
+    fn it_renders_several_conversions() {
+        let conversions = Conversions(vec![
+            conversion(),
+            Conversion {
+                converted: Decimal::from_i128_with_scale(5508, 2),
+                to: "USD",
+                ..conversion()
+            },
+        ]);
+
+        insta::assert_snapshot!(
+            "conversions_json",
+            render(&conversions, OutputFormat::Json, None)
+        );
+        insta::assert_snapshot!(
+            "conversions_csv",
+            render(&conversions, OutputFormat::Csv, None)
+        );
+        insta::assert_snapshot!(
+            "conversions_plain",
+            render(&conversions, OutputFormat::Plain, None)
+        );
+    }
+
+    #[test]
+    fn it_renders_amounts_for_humans() {
+        let conversions = Conversions(vec![
+            Conversion {
+                amount: Decimal::from(1234),
+                converted: Decimal::from_i128_with_scale(7405650, 2),
+                ..conversion()
+            },
+            Conversion {
+                amount: Decimal::from(1234),
+                converted: Decimal::from(184512),
+                to: "JPY",
+                ..conversion()
+            },
+        ]);
+        let de = Locale::find("de-DE");
+
+        assert_eq!(
+            "€50,00 -> ₱3,044.58 on 2023-05-06",
+            render(&conversion(), OutputFormat::Human, None)
+        );
+        assert_eq!(
+            "€50,00 -> ₱3.044,58 on 2023-05-06",
+            render(&conversion(), OutputFormat::Human, de)
+        );
+        insta::assert_snapshot!(
+            "conversions_human",
+            render(&conversions, OutputFormat::Human, de)
+        );
+    }
+
+    #[test]
+    fn it_renders_quotes() {
+        let quote = Quote {
+            amount: Decimal::from(1000),
+            from: "EUR",
+            to: "USD",
+            date: NaiveDate::from_ymd_opt(2023, 5, 4).unwrap(),
+            fallback: false,
+            mid_rate: Decimal::from_i128_with_scale(11074, 4),
+            effective_rate: Decimal::from_i128_with_scale(1096326, 6),
+            spread_percent: Decimal::ONE,
+            fees: Decimal::from(20),
+            mid_amount: Decimal::from_i128_with_scale(110740, 2),
+            customer_amount: Decimal::from_i128_with_scale(107440, 2),
+        };
+
+        insta::assert_snapshot!("quote_plain", render(&quote, OutputFormat::Plain, None));
+        insta::assert_snapshot!("quote_json", render(&quote, OutputFormat::Json, None));
+        insta::assert_snapshot!("quote_human", quote.human(Locale::find("de-DE").as_ref()));
+    }
+
+    #[test]
+    fn it_renders_averages() {
+        let averages = Averages {
+            from: "EUR",
+            to: "USD",
+            method: "calendar-days",
+            averages: vec![
+                Average {
+                    rate: Some(Decimal::from_i128_with_scale(1_098_333_333, 9)),
+                    days: 31,
+                    interpolated_days: 9,
+                    ..Average::missing(Period::Month {
+                        year: 2023,
+                        month: 5,
+                    })
+                    .unwrap()
+                },
+                Average::missing(Period::Month {
+                    year: 2023,
+                    month: 6,
+                })
+                .unwrap(),
+            ],
+        };
+
+        insta::assert_snapshot!(
+            "averages_plain",
+            render(&averages, OutputFormat::Plain, None)
+        );
+        insta::assert_snapshot!("averages_csv", render(&averages, OutputFormat::Csv, None));
+    }
+
+    #[test]
+    fn it_renders_closing_rates() {
+        let month = |month| Period::Month { year: 2023, month };
+        let closings = Closings {
+            from: "EUR",
+            to: "USD",
+            closings: vec![
+                Closing {
+                    date: NaiveDate::from_ymd_opt(2023, 4, 28),
+                    rate: Some(Decimal::from_i128_with_scale(10981, 4)),
+                    ..Closing::missing(month(4)).unwrap()
+                },
+                Closing {
+                    date: NaiveDate::from_ymd_opt(2023, 5, 12),
+                    rate: Some(Decimal::from_i128_with_scale(10892, 4)),
+                    ..Closing::missing(month(5)).unwrap()
+                },
+                Closing::missing(month(6)).unwrap(),
+            ],
+        };
+
+        insta::assert_snapshot!(
+            "closings_plain",
+            render(&closings, OutputFormat::Plain, None)
+        );
+        insta::assert_snapshot!("closings_csv", render(&closings, OutputFormat::Csv, None));
+    }
+
+    #[test]
+    fn it_renders_stats() {
+        let rate = |day, rate| StatsRate {
+            date: NaiveDate::from_ymd_opt(2023, 5, day).unwrap(),
+            rate: Decimal::from_i128_with_scale(rate, 4),
+        };
+        let stats = Stats {
+            from: "EUR",
+            to: "USD",
+            start: NaiveDate::from_ymd_opt(2023, 5, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2023, 5, 31).unwrap(),
+            observations: 9,
+            first: rate(2, 10965),
+            last: rate(12, 10892),
+            min: rate(12, 10892),
+            max: rate(4, 11074),
+            mean: Decimal::from_i128_with_scale(1_098_488_888_889, 12),
+            std_dev: Some(Decimal::from_i128_with_scale(6_403_253, 9)),
+            volatility_percent: Some(Decimal::from_i128_with_scale(6_741_512, 6)),
+            change_percent: Decimal::from_i128_with_scale(-665_754, 6),
+        };
+
+        insta::assert_snapshot!("stats_plain", render(&stats, OutputFormat::Plain, None));
+        insta::assert_snapshot!("stats_json", render(&stats, OutputFormat::Json, None));
+    }
+
+    #[test]
+    fn it_renders_history() {
+        let point = |day, open, high, low, close| HistoryPoint {
+            date: NaiveDate::from_ymd_opt(2023, 5, day).unwrap(),
+            open: Decimal::from_i128_with_scale(open, 4),
+            high: Decimal::from_i128_with_scale(high, 4),
+            low: Decimal::from_i128_with_scale(low, 4),
+            close: Decimal::from_i128_with_scale(close, 4),
+            observations: 5,
+            sma: None,
+            ema: None,
+        };
+        let history = History {
+            from: "EUR",
+            to: "USD",
+            resample: "weekly",
+            sma: Some(2),
+            ema: None,
+            points: vec![
+                point(1, 10965, 11074, 10965, 11014),
+                HistoryPoint {
+                    sma: Some(Decimal::from_i128_with_scale(109_720, 5)),
+                    ..point(8, 11037, 11037, 10892, 10892)
+                },
+            ],
+        };
+
+        insta::assert_snapshot!("history_plain", render(&history, OutputFormat::Plain, None));
+        insta::assert_snapshot!("history_csv", render(&history, OutputFormat::Csv, None));
+    }
+
+    #[test]
+    fn it_renders_charts() {
+        let rates = [
+            ("2023-05-02", 10965, true),
+            ("2023-05-03", 11043, true),
+            ("2023-05-04", 11074, true),
+            ("2023-05-05", 11014, true),
+            ("2023-05-06", 11022, false),
+            ("2023-05-07", 11029, false),
+            ("2023-05-08", 11037, true),
+            ("2023-05-09", 10959, true),
+            ("2023-05-10", 10950, true),
+            ("2023-05-11", 10930, true),
+            ("2023-05-12", 10892, true),
+        ];
+        let series = rates
+            .iter()
+            .map(|(date, rate, observed)| DatedRate {
+                date: date.parse().unwrap(),
+                rate: Decimal::from_i128_with_scale(*rate, 4),
+                status: match observed {
+                    true => RateStatus::Observed,
+                    false => RateStatus::Interpolated,
+                },
+            })
+            .collect();
+        let start = "2023-05-02".parse().unwrap();
+        let end = "2023-05-12".parse().unwrap();
+        let pairs = vec![ChartPair::new(iso::EUR, iso::USD, series)];
+        let chart = Chart::new(start, end, false, pairs, 24, 5);
+
+        insta::assert_snapshot!("chart_plain", render(&chart, OutputFormat::Plain, None));
+        insta::assert_snapshot!("chart_csv", render(&chart, OutputFormat::Csv, None));
+        insta::assert_snapshot!("chart_svg", chart.svg());
+    }
+
+    #[test]
+    fn it_renders_cross_rate_matrices() {
+        let rate = |rate: i128, scale| Some(Decimal::from_i128_with_scale(rate, scale));
+        let matrix = Matrix {
+            date: NaiveDate::from_ymd_opt(2023, 5, 4).unwrap(),
+            interpolated: false,
+            currencies: vec!["USD", "EUR", "CYP"],
+            rates: vec![
+                vec![Some(Decimal::ONE), rate(9_030_160, 7), None],
+                vec![rate(11074, 4), Some(Decimal::ONE), None],
+                vec![None, None, None],
+            ],
+        };
+
+        insta::assert_snapshot!("matrix_plain", render(&matrix, OutputFormat::Plain, None));
+        insta::assert_snapshot!("matrix_csv", render(&matrix, OutputFormat::Csv, None));
+        insta::assert_snapshot!("matrix_json", render(&matrix, OutputFormat::Json, None));
+    }
+
+    #[test]
+    fn it_renders_sync_reports() {
+        insta::assert_snapshot!("synced_json", render(&synced(), OutputFormat::Json, None));
+        insta::assert_snapshot!("synced_csv", render(&synced(), OutputFormat::Csv, None));
+
+        let summary = Synced {
+            revisions: None,
+            ..synced()
+        };
+        insta::assert_snapshot!(
+            "synced_summary_json",
+            render(&summary, OutputFormat::Json, None)
+        );
+        insta::assert_snapshot!(
+            "synced_summary_csv",
+            render(&summary, OutputFormat::Csv, None)
+        );
+    }
+
+    #[test]
+    fn it_renders_rates() {
+        insta::assert_snapshot!("rates_json", render(&rates(), OutputFormat::Json, None));
+        insta::assert_snapshot!("rates_csv", render(&rates(), OutputFormat::Csv, None));
+        insta::assert_snapshot!("rates_plain", render(&rates(), OutputFormat::Plain, None));
+    }
+
+    #[test]
+    fn it_renders_config_entries() {
+        let entries = ConfigEntries::new(
+            Path::new("/home/me/.config/moneyman/config.toml"),
+            vec![
+                ("to".to_string(), Some("EUR".to_string())),
+                ("http.proxy".to_string(), None),
+            ],
+        );
+
+        insta::assert_snapshot!("config_plain", render(&entries, OutputFormat::Plain, None));
+        insta::assert_snapshot!("config_csv", render(&entries, OutputFormat::Csv, None));
+    }
+
+    #[test]
+    fn it_renders_store_info() {
+        insta::assert_snapshot!("info_json", render(&info(), OutputFormat::Json, None));
+        insta::assert_snapshot!("info_plain", render(&info(), OutputFormat::Plain, None));
+    }
+}
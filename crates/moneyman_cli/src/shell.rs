@@ -0,0 +1,437 @@
+use std::path::Path;
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use moneyman::{ExchangeStore, Rounding, RoundingMode};
+use rusty_money::iso;
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    history::DefaultHistory,
+    Context, Editor, Helper, Highlighter, Hinter, Validator,
+};
+
+use crate::{
+    currency::Currency,
+    error::CliError,
+    locale::{parse_locale_arg, Locale},
+    output,
+    output::OutputFormat,
+    query::{self, Query},
+};
+
+const HELP: &str = "\
+Statements:
+  <AMOUNT> <CURRENCY> to <CURRENCY>... [<DATE>]   e.g 100 usd to jpy,gbp on 2020-03-01
+  rates [<CURRENCY>] [on <DATE>]                  e.g rates gbp
+  info                                            Show what's in the local data store
+  set fallback linear|off                         Interpolate missing rates, or don't
+  set output plain|human|json|csv|tsv             How results are printed
+  set locale <LOCALE>|off                         e.g de-DE, for `set output human`
+  set round exact|half-even|half-up|floor|...     How converted amounts are rounded
+  help                                            Show this
+  quit                                            Leave the shell. Ctrl-D works too
+
+Dates default to the latest one in the local data store. Relative dates like
+yesterday, last friday, -7d, or 2023-Q1-end work too.";
+
+/// Words the shell understands, besides currencies
+const KEYWORDS: [&str; 26] = [
+    "to",
+    "in",
+    "on",
+    "rates",
+    "info",
+    "set",
+    "fallback",
+    "output",
+    "round",
+    "locale",
+    "linear",
+    "off",
+    "help",
+    "quit",
+    "exit",
+    "plain",
+    "human",
+    "json",
+    "csv",
+    "tsv",
+    "exact",
+    "half-even",
+    "half-up",
+    "floor",
+    "ceiling",
+    "truncate",
+];
+
+/// What persists between statements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+    /// Whether conversions may use interpolated rates
+    pub fallback: bool,
+    pub output: OutputFormat,
+    /// Locale of `--output human`
+    pub locale: Option<Locale>,
+    /// How converted amounts are rounded
+    pub rounding: Rounding,
+    /// Used when a conversion has no target currency
+    pub to: Option<iso::Currency>,
+}
+
+/// A setting changed with `set`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    Fallback(bool),
+    Output(OutputFormat),
+    /// `None` writes amounts the way their currency usually is
+    Locale(Option<Locale>),
+    Round(RoundingMode),
+}
+
+/// A line typed into the shell
+#[derive(Clone, Debug, PartialEq)]
+pub enum Statement {
+    Convert(Query),
+    Rates {
+        base: Option<iso::Currency>,
+        on: Option<NaiveDate>,
+    },
+    Info,
+    Set(Setting),
+    Help,
+    Quit,
+    /// A blank line
+    Nothing,
+}
+
+fn parse_currency(word: &str) -> Result<iso::Currency, String> {
+    Currency::find(word)
+        .map(|currency| currency.0)
+        .ok_or_else(|| format!("unknown currency {word:?}"))
+}
+
+/// Parses an optional trailing `on <DATE>`
+fn parse_on(words: &[&str], today: NaiveDate) -> Result<Option<NaiveDate>, String> {
+    match words {
+        [] => Ok(None),
+        ["on", date @ ..] if !date.is_empty() => {
+            query::parse_date(&date.join(" "), today).map(Some)
+        }
+        _ => Err(format!("unexpected {:?}", words.join(" "))),
+    }
+}
+
+/// Parses a line typed into the shell. Keywords and currencies are case
+/// insensitive. Relative dates are relative to `today`. Conversions without a
+/// target currency convert to `default_to` if there's one.
+pub fn parse(
+    line: &str,
+    today: NaiveDate,
+    default_to: Option<iso::Currency>,
+) -> Result<Statement, String> {
+    let line = line.to_lowercase();
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    match words.as_slice() {
+        [] => Ok(Statement::Nothing),
+        ["quit" | "exit"] => Ok(Statement::Quit),
+        ["help" | "?"] => Ok(Statement::Help),
+        ["info"] => Ok(Statement::Info),
+        ["set", "fallback", "linear" | "on"] => Ok(Statement::Set(Setting::Fallback(true))),
+        ["set", "fallback", "off" | "none"] => Ok(Statement::Set(Setting::Fallback(false))),
+        ["set", "output", format] => OutputFormat::from_str(format, true)
+            .map(|format| Statement::Set(Setting::Output(format)))
+            .map_err(|_| format!("unknown output {format:?}")),
+        ["set", "locale", "off" | "none"] => Ok(Statement::Set(Setting::Locale(None))),
+        ["set", "locale", tag] => parse_locale_arg(tag)
+            .map(|locale| Statement::Set(Setting::Locale(Some(locale)))),
+        ["set", "round", mode] => RoundingMode::from_name(mode)
+            .map(|mode| Statement::Set(Setting::Round(mode)))
+            .ok_or_else(|| format!("unknown rounding mode {mode:?}")),
+        ["set", ..] => Err(
+            "expected `set fallback linear|off`, `set output FORMAT`, `set locale LOCALE|off`, or `set round MODE`".into(),
+        ),
+        ["rates", "on", ..] => Ok(Statement::Rates {
+            base: None,
+            on: parse_on(&words[1..], today)?,
+        }),
+        ["rates", base, rest @ ..] => Ok(Statement::Rates {
+            base: Some(parse_currency(base)?),
+            on: parse_on(rest, today)?,
+        }),
+        ["rates"] => Ok(Statement::Rates {
+            base: None,
+            on: None,
+        }),
+        [first, ..] if first.starts_with(|c: char| c.is_ascii_digit() || c == '-') => {
+            query::parse(&words, today, default_to).map(Statement::Convert)
+        }
+        _ => Err(format!(
+            "don't know what {:?} means. Type `help` to see what you can do",
+            line.trim()
+        )),
+    }
+}
+
+/// Completes keywords, and the ISO alpha codes of the currencies in the
+/// store.
+#[derive(Helper, Highlighter, Hinter, Validator)]
+struct ShellHelper {
+    words: Vec<String>,
+}
+
+impl ShellHelper {
+    fn new(currencies: impl IntoIterator<Item = String>) -> Self {
+        let mut words: Vec<String> = currencies
+            .into_iter()
+            .chain(KEYWORDS.iter().map(|keyword| keyword.to_string()))
+            .collect();
+        words.sort();
+        words.dedup();
+
+        ShellHelper { words }
+    }
+
+    /// Every word starting with `prefix`, in the same case as `prefix`
+    fn candidates(&self, prefix: &str) -> Vec<String> {
+        let uppercase = !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_uppercase());
+
+        self.words
+            .iter()
+            .filter(|word| word.to_lowercase().starts_with(&prefix.to_lowercase()))
+            .map(|word| match uppercase {
+                true => word.to_uppercase(),
+                false => word.to_lowercase(),
+            })
+            .collect()
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos]
+            .rfind(char::is_whitespace)
+            .map_or(0, |index| index + 1);
+        let candidates = self
+            .candidates(&line[start..pos])
+            .into_iter()
+            .map(|word| Pair {
+                display: word.clone(),
+                replacement: word,
+            })
+            .collect();
+
+        Ok((start, candidates))
+    }
+}
+
+/// Runs a single statement. Returns `false` once the shell should stop.
+fn eval(
+    store: &ExchangeStore,
+    settings: &mut Settings,
+    statement: Statement,
+) -> Result<bool, CliError> {
+    match statement {
+        Statement::Convert(query) => crate::print_conversions(
+            store,
+            query.amount,
+            &query.from,
+            &query.to,
+            &crate::ConvertOptions {
+                on: query.on,
+                fallback: settings.fallback,
+                rounding: settings.rounding,
+            },
+            settings.output,
+            settings.locale,
+        )?,
+        Statement::Rates { base, on } => output::print(
+            &crate::rates(store, base, on)?,
+            settings.output,
+            settings.locale,
+        )?,
+        Statement::Info => output::print(
+            &crate::report::Info::from(&store.stats()?),
+            settings.output,
+            settings.locale,
+        )?,
+        Statement::Set(Setting::Fallback(fallback)) => settings.fallback = fallback,
+        Statement::Set(Setting::Output(format)) => settings.output = format,
+        Statement::Set(Setting::Locale(locale)) => settings.locale = locale,
+        Statement::Set(Setting::Round(mode)) => settings.rounding.mode = mode,
+        Statement::Help => output::print_line(HELP)?,
+        Statement::Quit => return Ok(false),
+        Statement::Nothing => (),
+    }
+
+    Ok(true)
+}
+
+fn readline_to_io(err: ReadlineError) -> CliError {
+    match err {
+        ReadlineError::Io(err) => CliError::Io(err),
+        err => CliError::Io(std::io::Error::other(err)),
+    }
+}
+
+/// Reads statements until `quit`, or Ctrl-D, with the same store open the
+/// whole time. Errors are printed without leaving the shell. History is
+/// kept in `history_path`.
+pub fn run(
+    store: &ExchangeStore,
+    history_path: &Path,
+    mut settings: Settings,
+) -> Result<(), CliError> {
+    let currencies = store
+        .stats()?
+        .currencies
+        .into_iter()
+        .map(|coverage| coverage.currency)
+        .chain([iso::EUR.iso_alpha_code.to_string()]);
+
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new().map_err(readline_to_io)?;
+    editor.set_helper(Some(ShellHelper::new(currencies)));
+    // There's no history on the first run
+    let _ = editor.load_history(history_path);
+
+    eprintln!("moneyman shell. Type `help` to see what you can do, or `quit` to leave.");
+
+    loop {
+        let line = match editor.readline("moneyman> ") {
+            Ok(line) => line,
+            // Ctrl-C clears the line, like most shells
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(readline_to_io(err)),
+        };
+
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+
+        let keep_going = match parse(&line, query::today(), settings.to) {
+            Ok(statement) => match eval(store, &mut settings, statement) {
+                Ok(keep_going) => keep_going,
+                // Nothing else can be printed
+                Err(CliError::Io(err)) if err.kind() == std::io::ErrorKind::BrokenPipe => false,
+                Err(err) => {
+                    eprintln!("{}", err.render(settings.output));
+
+                    true
+                }
+            },
+            Err(message) => {
+                eprintln!("{message}");
+
+                true
+            }
+        };
+
+        if !keep_going {
+            break;
+        }
+    }
+
+    editor.save_history(history_path).map_err(readline_to_io)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    fn parse(line: &str) -> Result<Statement, String> {
+        super::parse(line, date(2023, 5, 10).unwrap(), None)
+    }
+
+    #[test]
+    fn it_parses_conversions() {
+        assert_eq!(
+            Ok(Statement::Convert(Query {
+                amount: Decimal::from(100),
+                from: *iso::USD,
+                to: vec![*iso::JPY],
+                on: date(2020, 3, 1),
+            })),
+            parse("100 usd to jpy on 2020-03-01")
+        );
+        assert_eq!(
+            Ok(Statement::Convert(Query {
+                amount: Decimal::from_i128_with_scale(105, 1),
+                from: *iso::GBP,
+                to: vec![*iso::EUR, *iso::USD],
+                on: date(2023, 5, 9),
+            })),
+            parse("  10.5 GBP in eur,usd yesterday ")
+        );
+        assert!(parse("100 usd to xxx").is_err());
+        assert!(parse("100 usd to jpy on someday").is_err());
+    }
+
+    #[test]
+    fn it_parses_rates() {
+        assert_eq!(
+            Ok(Statement::Rates {
+                base: Some(*iso::GBP),
+                on: None,
+            }),
+            parse("rates gbp")
+        );
+        assert_eq!(
+            Ok(Statement::Rates {
+                base: None,
+                on: date(2023, 5, 5),
+            }),
+            parse("rates on last friday")
+        );
+    }
+
+    #[test]
+    fn it_parses_settings() {
+        assert_eq!(
+            Ok(Statement::Set(Setting::Fallback(true))),
+            parse("set fallback linear")
+        );
+        assert_eq!(
+            Ok(Statement::Set(Setting::Output(OutputFormat::Json))),
+            parse("set output JSON")
+        );
+        assert_eq!(
+            Ok(Statement::Set(Setting::Round(RoundingMode::HalfUp))),
+            parse("set round half-up")
+        );
+        assert_eq!(
+            Ok(Statement::Set(Setting::Locale(Locale::find("de-DE")))),
+            parse("set locale de-DE")
+        );
+        assert_eq!(
+            Ok(Statement::Set(Setting::Locale(None))),
+            parse("set locale off")
+        );
+        assert!(parse("set locale xx-XX").is_err());
+        assert!(parse("set fallback cubic").is_err());
+        assert_eq!(Ok(Statement::Nothing), parse("   "));
+    }
+
+    #[test]
+    fn it_completes_currencies_in_the_same_case() {
+        let helper = ShellHelper::new(["USD".to_string(), "UAH".to_string(), "JPY".to_string()]);
+
+        assert_eq!(vec!["usd"], helper.candidates("us"));
+        assert_eq!(vec!["UAH", "USD"], helper.candidates("U"));
+        assert_eq!(vec!["rates"], helper.candidates("ra"));
+    }
+}